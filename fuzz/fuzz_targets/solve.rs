@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sudoku::prelude::*;
+
+// Run with `cargo fuzz run solve`. The boards may be invalid, the solver
+// must not panic with any of them.
+fuzz_target!(|board: SudokuBoard| {
+    let line = board.to_line_string();
+    assert_eq!(SudokuBoard::try_from(line.as_str()), Ok(board.clone()));
+
+    // the node budget keeps conflicting boards from searching the whole tree
+    let (count, _) = board.count_solutions_bounded(2, 10_000);
+    assert!(count <= 2);
+
+    board.clue_complement();
+    board.grade();
+});
@@ -1,12 +1,13 @@
-use clap::{crate_authors, crate_name, crate_version, App, Arg, ArgGroup, ArgMatches, SubCommand};
+use clap::{crate_name, crate_version, App, Arg, ArgGroup, ArgMatches, SubCommand};
 use std::convert::TryInto;
 use std::fs::File;
 use std::io::{self, stdout, BufRead, BufReader, BufWriter, Write};
+use std::iter;
 use std::path::Path;
 use sudoku::prelude::*;
 
 fn integer_validator(val: String) -> Result<(), String> {
-    if val.chars().all(|c| c.is_digit(10)) {
+    if val.chars().all(|c| c.is_ascii_digit()) {
         Ok(())
     } else {
         Err(format!("\"{}\" is not valid number", val))
@@ -17,11 +18,12 @@ enum OutputFormat {
     Pretty,
     Line,
     Csv,
+    Json,
 }
 
 impl OutputFormat {
     const fn get_all() -> &'static [&'static str] {
-        &["pretty", "line", "csv"]
+        &["pretty", "line", "csv", "json"]
     }
 }
 
@@ -32,11 +34,14 @@ impl TryFrom<&str> for OutputFormat {
             "pretty" => Ok(Self::Pretty),
             "csv" => Ok(Self::Csv),
             "line" => Ok(Self::Line),
+            "json" => Ok(Self::Json),
             _ => Err("Unknown format"),
         }
     }
 }
 
+// fields are only read through `Debug` when `main` returns the error
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Error {
     IoError(io::Error),
@@ -55,9 +60,72 @@ impl From<&'static str> for Error {
     }
 }
 
+/// Quotes and escapes a string to be used as a JSON value
+fn json_string(val: &str) -> String {
+    let mut quoted = String::with_capacity(val.len() + 2);
+    quoted.push('"');
+    for c in val.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Cell of a position as `r<row>c<column>`, counting from 1
+fn cell_name(pos: usize) -> String {
+    format!("r{}c{}", pos / 9 + 1, pos % 9 + 1)
+}
+
+/// Writes JSON objects as the items of a single array as they come, one
+/// object per line
+#[derive(Default)]
+struct JsonArray {
+    len: usize,
+}
+
+impl JsonArray {
+    fn push(&mut self, output: &mut BufWriter<Box<dyn Write>>, item: String) -> io::Result<()> {
+        let separator = if self.len == 0 { "[" } else { "," };
+        self.len += 1;
+        write!(output, "{}\n  {}", separator, item)
+    }
+
+    fn finish(self, output: &mut BufWriter<Box<dyn Write>>) -> io::Result<()> {
+        if self.len == 0 {
+            writeln!(output, "[]")
+        } else {
+            writeln!(output, "\n]")
+        }
+    }
+}
+
+fn puzzle_json(puzzle: &SudokuPuzzle) -> String {
+    let s = &puzzle.stats;
+    format!(
+        "{{\"puzzle\":\"{:#}\",\"solution\":{},\"stats\":{{\"seed\":{},\"empty_positions\":{},\"difficulty\":\"{:#}\",\"possible_solutions\":{},\"board_time_us\":{},\"puzzle_time_us\":{}}}}}",
+        puzzle.puzzle,
+        puzzle
+            .solution
+            .as_ref()
+            .map_or_else(|| String::from("null"), |sol| format!("\"{:#}\"", sol)),
+        json_string(&s.seed),
+        s.empty_positions,
+        s.difficulty,
+        s.possible_solutions
+            .map_or_else(|| String::from("null"), |count| count.to_string()),
+        s.times.0.as_micros(),
+        s.times.1.as_micros(),
+    )
+}
+
 fn main() -> Result<(), Error> {
     let matches = App::new(crate_name!())
-        .author(crate_authors!("\n"))
+        .author(env!("CARGO_PKG_AUTHORS"))
         .version(crate_version!())
         .about("Sudoku solver and generator")
         .arg(
@@ -109,16 +177,22 @@ fn main() -> Result<(), Error> {
                 .group(
                     ArgGroup::with_name("multiple_solutions")
                         .args(&["count_solutions", "all"])
+                        .conflicts_with("from_seed")
+                )
+                .arg(
+                    Arg::with_name("validate")
+                        .help("Checks that no row, column or box has repeated values before solving, reporting the conflicting cells. Exits with an error if any sudoku has them")
+                        .long("validate")
                 )
                 .arg(
                     Arg::with_name("sudoku")
-                        .help("Sudoku string or string separared by a newline")
+                        .help("Sudoku string or string separared by a newline. Without sudokus or files, they are read from the standard input, 1 line per sudoku")
                         .empty_values(false)
                         .multiple(true)
                 )
                 .arg(
                     Arg::with_name("file")
-                        .help("Path to input file, 1 line per sudoku")
+                        .help("Path to input file, 1 line per sudoku. `-` reads the standard input")
                         .short("f")
                         .long("file")
                         .value_name("FILE")
@@ -133,13 +207,43 @@ fn main() -> Result<(), Error> {
                         .long("seed")
                         .takes_value(true)
                         .value_name("SEED")
-                        .conflicts_with_all(&["all", "count_solutions"])
                         .multiple(true)
                 )
                 .group(
                     ArgGroup::with_name("input")
                         .args(&["sudoku", "file", "from_seed"])
-                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("grade")
+                .about("Grade the difficulty of sudokus by the techniques needed to solve them")
+                .arg(
+                    Arg::with_name("max_technique")
+                        .help("Hardest technique to use, anything harder requires guessing")
+                        .long("max-technique")
+                        .takes_value(true)
+                        .value_name("TECHNIQUE")
+                        .possible_values(Technique::get_all_ids())
+                )
+                .arg(
+                    Arg::with_name("sudoku")
+                        .help("Sudoku string or string separared by a newline. Without sudokus or files, they are read from the standard input, 1 line per sudoku")
+                        .empty_values(false)
+                        .multiple(true)
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("Path to input file, 1 line per sudoku. `-` reads the standard input")
+                        .short("f")
+                        .long("file")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .empty_values(false)
+                        .multiple(true)
+                )
+                .group(
+                    ArgGroup::with_name("input")
+                        .args(&["sudoku", "file"])
                 )
         )
         .subcommand(
@@ -178,13 +282,21 @@ fn main() -> Result<(), Error> {
                 )
                 .arg(
                     Arg::with_name("from_seed")
-                        .help("Generate a puzzle from a seed. (The dificulty and uniqueness of solution must match to get the same puzzle)")
+                        .help("Generate a puzzle from a seed. (The dificulty and uniqueness of solution must match to get the same puzzle, or use --code). With an amount, the next puzzles use the seed followed by -1, -2...")
                         .next_line_help(true)
                         .short("s")
                         .long("seed")
                         .takes_value(true)
                         .value_name("SEED")
                 )
+                .arg(
+                    Arg::with_name("from_code")
+                        .help("Generate again the puzzle of a share code, with its seed, difficulty and uniqueness of solution")
+                        .long("code")
+                        .takes_value(true)
+                        .value_name("CODE")
+                        .conflicts_with_all(&["from_seed", "allow_multiple"])
+                )
         )
         .get_matches();
 
@@ -211,6 +323,7 @@ fn main() -> Result<(), Error> {
 
     match matches.subcommand() {
         ("solve", Some(sub_m)) => handle_solve(sub_m, &mut output, format, multiple_limit)?,
+        ("grade", Some(sub_m)) => handle_grade(sub_m, &mut output, format)?,
         ("generate", Some(sub_m)) => handle_generate(sub_m, &mut output, format, multiple_limit)?,
         _ => {
             let puzzle = SudokuPuzzle::prepare()
@@ -223,6 +336,11 @@ fn main() -> Result<(), Error> {
                     writeln!(&mut output, "{}\n{:#}", SudokuPuzzle::csv_head(), puzzle)?
                 }
                 OutputFormat::Line => writeln!(&mut output, "{:#}", puzzle.puzzle)?,
+                OutputFormat::Json => {
+                    let mut json = JsonArray::default();
+                    json.push(&mut output, puzzle_json(&puzzle))?;
+                    json.finish(&mut output)?
+                }
             }
         }
     }
@@ -232,6 +350,70 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Lazily reads the non blank lines of `reader`, trimmed
+fn read_lines<'a>(
+    reader: impl BufRead + 'a,
+) -> Box<dyn Iterator<Item = Result<String, Error>> + 'a> {
+    Box::new(reader.lines().filter_map(|line| match line {
+        Ok(line) => {
+            let line = line.trim();
+            if line.is_empty() {
+                None
+            } else {
+                Some(Ok(line.to_string()))
+            }
+        }
+        Err(err) => Some(Err(err.into())),
+    }))
+}
+
+/// Lazily reads the inputs one by one, so big files are never fully loaded.
+/// Without inputs in the arguments, they are read from the standard input.
+fn read_inputs<'a>(
+    matches: &'a ArgMatches,
+) -> Box<dyn Iterator<Item = Result<String, Error>> + 'a> {
+    if matches.is_present("sudoku") {
+        Box::new(
+            matches
+                .values_of("sudoku")
+                .unwrap()
+                .map(|input| Ok(String::from(input))),
+        )
+    } else if matches.is_present("file") {
+        Box::new(
+            matches
+                .values_of_os("file")
+                .unwrap()
+                .map(Path::new)
+                .flat_map(|path| -> Box<dyn Iterator<Item = Result<String, Error>>> {
+                    if path == Path::new("-") {
+                        return read_lines(BufReader::new(io::stdin()));
+                    }
+                    if !path.is_file() {
+                        return Box::new(iter::once(Err(Error::ErrorMessage(
+                            "Input path is not a file",
+                        ))));
+                    }
+                    let file = match File::open(path) {
+                        Ok(file) => file,
+                        Err(err) => return Box::new(iter::once(Err(err.into()))),
+                    };
+
+                    read_lines(BufReader::new(file))
+                }),
+        )
+    } else if matches.is_present("from_seed") {
+        Box::new(
+            matches
+                .values_of("from_seed")
+                .unwrap()
+                .map(|seed| Ok(String::from(seed))),
+        )
+    } else {
+        read_lines(BufReader::new(io::stdin()))
+    }
+}
+
 fn handle_solve(
     matches: &ArgMatches,
     output: &mut BufWriter<Box<dyn Write>>,
@@ -242,77 +424,103 @@ fn handle_solve(
     let from_seeds = matches.is_present("from_seed");
     let count_solutions = matches.is_present("count_solutions");
     let all_solutions = matches.is_present("all");
-    eprintln!("Parsing inputs...");
-    let inputs: Vec<String> = if matches.is_present("sudoku") {
-        matches
-            .values_of("sudoku")
-            .unwrap()
-            .map(String::from)
-            .collect()
-    } else if matches.is_present("file") {
-        matches
-            .values_of_os("file")
-            .unwrap()
-            .map(Path::new)
-            .map(|path| {
-                if !path.is_file() {
-                    return Err(Error::ErrorMessage("Input path is not a file"));
-                }
-                let file = File::open(path)?;
-                let buffered = BufReader::new(file);
-
-                let mut parsed_lines = Vec::new();
-
-                for line in buffered.lines() {
-                    let line = line?;
-                    let line = line.trim();
-                    if !line.is_empty() {
-                        parsed_lines.push(line.to_string())
-                    }
-                }
-
-                Ok(parsed_lines)
-            })
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .flatten()
-            .collect()
-    } else if matches.is_present("from_seed") {
-        matches
-            .values_of("from_seed")
-            .unwrap()
-            .map(String::from)
-            .collect()
-    } else {
-        panic!("No input for solve");
-    };
-
-    eprintln!("Start solving {} sudoku(s)", inputs.len());
+    let validate = matches.is_present("validate");
+    let mut any_conflict = false;
+    eprintln!("Start solving sudoku(s)");
 
     if matches!(format, Csv) {
         writeln!(output, "input,result")?;
     }
+    let mut json = JsonArray::default();
 
-    for input in &inputs {
+    for input in read_inputs(matches) {
+        let input = &input?;
         let mut board = if from_seeds {
-            SudokuBoard::generate_from_seed(&input)
+            SudokuBoard::generate_from_seed(input)
         } else {
-            SudokuBoard::try_from(input.as_str())?
+            // a bad input does not stop the rest from being solved
+            match SudokuBoard::try_from(input.as_str()) {
+                Ok(board) => board,
+                Err(err) => {
+                    match format {
+                        Pretty => writeln!(output, "{}:\n\tParse error: {}", input, err)?,
+                        Line | Csv => writeln!(output, "{},parse_error", input)?,
+                        Json => json.push(
+                            output,
+                            format!(
+                                "{{\"input\":{},\"error\":{}}}",
+                                json_string(input),
+                                json_string(&err.to_string())
+                            ),
+                        )?,
+                    }
+                    continue;
+                }
+            }
         };
 
+        if validate {
+            let conflicts = board.conflicts();
+            if !conflicts.is_empty() {
+                any_conflict = true;
+                let pairs: Vec<String> = conflicts
+                    .iter()
+                    .map(|&(a, b)| format!("{} and {}", cell_name(a), cell_name(b)))
+                    .collect();
+                match format {
+                    Pretty => writeln!(
+                        output,
+                        "{}:\n\tRepeated values in {}",
+                        input,
+                        pairs.join(", ")
+                    )?,
+                    Line | Csv => writeln!(output, "{},conflict", input)?,
+                    Json => {
+                        let cells: Vec<String> = conflicts
+                            .iter()
+                            .map(|&(a, b)| format!("[\"{}\",\"{}\"]", cell_name(a), cell_name(b)))
+                            .collect();
+                        json.push(
+                            output,
+                            format!(
+                                "{{\"input\":{},\"error\":\"Repeated values\",\"conflicts\":[{}]}}",
+                                json_string(input),
+                                cells.join(",")
+                            ),
+                        )?
+                    }
+                }
+                continue;
+            }
+        }
+
         #[allow(clippy::collapsible_else_if)]
         if all_solutions {
             let solutions = board.solve_all(multiple_limit);
-            if solutions.is_empty() {
+            if matches!(format, Json) {
+                let list: Vec<String> = solutions
+                    .iter()
+                    .map(|sol| format!("\"{:#}\"", sol))
+                    .collect();
+                json.push(
+                    output,
+                    format!(
+                        "{{\"input\":{},\"solutions\":[{}],\"solutions_count\":{}}}",
+                        json_string(input),
+                        list.join(","),
+                        solutions.len()
+                    ),
+                )?;
+            } else if solutions.is_empty() {
                 match format {
                     Pretty => writeln!(output, "{}:\n\tNo solution", input)?,
-                    Line | Csv => writeln!(output, "{},no_solution", input)?,
+                    _ => writeln!(output, "{},no_solution", input)?,
                 }
             } else {
                 for sol in solutions {
                     match format {
                         Pretty => writeln!(output, "{}:\n{}", input, sol)?,
-                        Line | Csv => writeln!(output, "{},{:#}", input, sol)?,
+                        _ => writeln!(output, "{},{:#}", input, sol)?,
                     }
                 }
             }
@@ -321,22 +529,126 @@ fn handle_solve(
             match format {
                 Pretty => writeln!(output, "{}:\n\t{} solutions", input, count)?,
                 Line | Csv => writeln!(output, "{},{}", input, count)?,
+                Json => json.push(
+                    output,
+                    format!(
+                        "{{\"input\":{},\"solutions_count\":{}}}",
+                        json_string(input),
+                        count
+                    ),
+                )?,
             }
         } else {
             if board.solve() {
                 match format {
                     Pretty => writeln!(output, "{}:\n{}", input, board)?,
                     Line | Csv => writeln!(output, "{},{:#}", input, board)?,
+                    Json => json.push(
+                        output,
+                        format!(
+                            "{{\"input\":{},\"solution\":\"{:#}\"}}",
+                            json_string(input),
+                            board
+                        ),
+                    )?,
                 }
             } else {
                 match format {
                     Pretty => writeln!(output, "{}:\n\tNo solution", input)?,
                     Line | Csv => writeln!(output, "{},no_solution", input)?,
+                    Json => json.push(
+                        output,
+                        format!("{{\"input\":{},\"solution\":null}}", json_string(input)),
+                    )?,
+                }
+            }
+        }
+    }
+
+    if matches!(format, Json) {
+        json.finish(output)?;
+    }
+
+    if any_conflict {
+        output.flush()?;
+        return Err(Error::ErrorMessage("Some sudokus have repeated values"));
+    }
+
+    Ok(())
+}
+
+fn handle_grade(
+    matches: &ArgMatches,
+    output: &mut BufWriter<Box<dyn Write>>,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    use OutputFormat::*;
+    let max_technique = match matches.value_of("max_technique") {
+        Some(id) => Technique::try_from(id)?,
+        None => *Technique::all().last().unwrap(),
+    };
+
+    eprintln!("Start grading sudoku(s)");
+
+    if matches!(format, Csv) {
+        writeln!(output, "input,difficulty,technique")?;
+    }
+    let mut json = JsonArray::default();
+
+    for input in read_inputs(matches) {
+        let input = &input?;
+        let board = match SudokuBoard::try_from(input.as_str()) {
+            Ok(board) => board,
+            Err(err) => {
+                match format {
+                    Pretty => writeln!(output, "{}:\n\tParse error: {}", input, err)?,
+                    Line | Csv => writeln!(output, "{},parse_error", input)?,
+                    Json => json.push(
+                        output,
+                        format!(
+                            "{{\"input\":{},\"error\":{}}}",
+                            json_string(input),
+                            json_string(&err.to_string())
+                        ),
+                    )?,
                 }
+                continue;
             }
+        };
+
+        let grade = board.grade_with(max_technique);
+        let technique = if grade.requires_guessing {
+            "guessing"
+        } else {
+            grade.hardest_technique.map_or("none", |t| t.as_str())
+        };
+        match format {
+            Pretty => writeln!(
+                output,
+                "{}:\n\tDifficulty: {}\n\tHardest technique: {}",
+                input, grade.difficulty, technique
+            )?,
+            Line | Csv => writeln!(output, "{},{:#},{}", input, grade.difficulty, technique)?,
+            Json => json.push(
+                output,
+                format!(
+                    "{{\"input\":{},\"difficulty\":\"{:#}\",\"technique\":{}}}",
+                    json_string(input),
+                    grade.difficulty,
+                    if technique == "none" {
+                        String::from("null")
+                    } else {
+                        json_string(technique)
+                    }
+                ),
+            )?,
         }
     }
 
+    if matches!(format, Json) {
+        json.finish(output)?;
+    }
+
     Ok(())
 }
 
@@ -352,21 +664,26 @@ fn handle_generate(
         .parse()
         .expect("Invalid amount, however it pass the validator");
 
-    let mut builder = SudokuPuzzle::prepare()
-        .with_difficulty(
-            matches
-                .value_of("difficulty")
-                .expect("No difficulty, not even default.")
-                .try_into()
-                .expect("Invalid difficulty, however it is included in possible values."),
-        )
-        .unique_solution(!matches.is_present("allow_multiple"))
-        .show_solution(matches.is_present("show_solution"))
-        .count_solutions(matches.is_present("count_solutions"))
-        .max_count_solutions(multiple_limit);
-    if let Some(seed) = matches.value_of("from_seed") {
-        builder = builder.with_seed(seed);
+    let builder = if let Some(code) = matches.value_of("from_code") {
+        Generator::from_share_code(code)?
+    } else {
+        let builder = SudokuPuzzle::prepare()
+            .with_difficulty(
+                matches
+                    .value_of("difficulty")
+                    .expect("No difficulty, not even default.")
+                    .try_into()
+                    .expect("Invalid difficulty, however it is included in possible values."),
+            )
+            .unique_solution(!matches.is_present("allow_multiple"));
+        match matches.value_of("from_seed") {
+            Some(seed) => builder.with_seed(seed),
+            None => builder,
+        }
     }
+    .show_solution(matches.is_present("show_solution"))
+    .count_solutions(matches.is_present("count_solutions"))
+    .max_count_solutions(multiple_limit);
 
     eprintln!("Generating puzzles...");
 
@@ -374,8 +691,8 @@ fn handle_generate(
         writeln!(output, "{}", SudokuPuzzle::csv_head())?;
     }
 
-    for _ in 0..amount {
-        let puzzle = builder.generate();
+    let mut json = JsonArray::default();
+    for puzzle in builder.generate_batch(amount) {
         match format {
             OutputFormat::Pretty => writeln!(output, "{}", puzzle)?,
             OutputFormat::Csv => writeln!(output, "{:#}", puzzle)?,
@@ -386,8 +703,13 @@ fn handle_generate(
                 }
                 writeln!(output)?;
             }
+            OutputFormat::Json => json.push(output, puzzle_json(&puzzle))?,
         }
     }
 
+    if matches!(format, OutputFormat::Json) {
+        json.finish(output)?;
+    }
+
     Ok(())
 }
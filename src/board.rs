@@ -2,12 +2,13 @@
 //! a solved one, printing.
 
 use super::{N, N2, SIZE};
+use crate::alloc_prelude::*;
+use crate::killer::Cage;
 use crate::pos_util::*;
 
-use std::collections::HashSet;
-use std::convert::TryFrom;
-use std::convert::TryInto;
-use std::fmt;
+use core::convert::TryFrom;
+use core::convert::TryInto;
+use core::fmt;
 
 use rand::prelude::*;
 use rand_pcg::Pcg64;
@@ -31,17 +32,48 @@ use rand_seeder::Seeder;
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct SudokuBoard([u8; SIZE]);
 
+/// Possible values of each position, as a bitmask where bit `n` is set if
+/// the value `n + 1` is possible.
 #[derive(Clone)]
-struct Domains {
-    domains: [[bool; N2]; SIZE],
-    empty_positions: HashSet<usize>,
+pub(crate) struct Domains {
+    pub(crate) domains: [u16; SIZE],
+    empty_positions: PositionSet,
+    variant: Variant,
+    /// Cages of a killer sudoku, empty in the rest
+    pub(crate) cages: Vec<Cage>,
+}
+
+/// Domain with all the values possible
+const ALL_VALUES: u16 = (1 << N2) - 1;
+
+/// Bit of a value in a domain
+const fn value_bit(value: u8) -> u16 {
+    1 << (value - 1)
+}
+
+/// Bits of the values one less and one more than `value` in a domain
+const fn consecutive_bits(value: u8) -> u16 {
+    let bit = value_bit(value);
+    ((bit << 1) | (bit >> 1)) & ALL_VALUES
+}
+
+/// Checks if `a` and `b` are two consecutive values, ignoring empty positions
+fn are_consecutive(a: u8, b: u8) -> bool {
+    a != 0 && b != 0 && (a as i8 - b as i8).abs() == 1
 }
 
 impl Domains {
     pub fn calculate_domains(board: &SudokuBoard) -> Self {
+        Self::calculate_variant_domains(board, Variant::Classic)
+    }
+
+    /// Like [Domains::calculate_domains], with the rules of `variant`
+    pub fn calculate_variant_domains(board: &SudokuBoard, variant: Variant) -> Self {
         let mut d = Self {
-            domains: [[true; N2]; SIZE],
-            empty_positions: HashSet::new(),
+            domains: [ALL_VALUES; SIZE],
+            empty_positions: PositionSet::default(),
+            variant,
+            cages: Vec::new(),
         };
 
         // for each cell
@@ -49,7 +81,7 @@ impl Domains {
             // if the cell is assigned
             if value != 0 {
                 // set all of its possible values to false
-                d.domains[pos].fill(false);
+                d.domains[pos] = 0;
                 // update the domains as if the value was just assigned
                 d.update_domains(pos, value);
             } else {
@@ -61,14 +93,73 @@ impl Domains {
 
     pub fn update_domains(&mut self, pos: usize, value: u8) {
         assert!(value > 0);
-        let value = (value - 1) as usize;
+        let bit = value_bit(value);
 
-        // in all conflicting indexes (row, col, group) mark the new value as false
-        for p in adjacent_positions(pos) {
-            self.domains[p][value] = false;
+        // in all conflicting indexes (row, col, group) clear the new value
+        for p in self.peers(pos) {
+            self.domains[p] &= !bit;
+        }
+        // and the consecutive values from the neighbors that forbid them
+        for p in self.variant.consecutive_peers(pos) {
+            self.domains[p] &= !consecutive_bits(value);
+        }
+
+        self.empty_positions.remove(pos);
+    }
+
+    /// Like [Domains::update_domains], but returns the positions whose
+    /// domain changed with the values removed from them, to undo it with
+    /// [Domains::undo_update].
+    pub fn update_domains_logged(&mut self, pos: usize, value: u8) -> Vec<(usize, u16)> {
+        assert!(value > 0);
+        let bit = value_bit(value);
+
+        let mut changes = self
+            .peers(pos)
+            .map(|p| (p, self.domains[p] & bit))
+            .chain(
+                self.variant
+                    .consecutive_peers(pos)
+                    .map(|p| (p, self.domains[p] & consecutive_bits(value))),
+            )
+            .collect::<Vec<_>>();
+        changes.retain(|&(_, removed)| removed != 0);
+        for &(p, removed) in &changes {
+            self.domains[p] &= !removed;
+        }
+
+        self.empty_positions.remove(pos);
+        changes
+    }
+
+    /// Undoes [Domains::update_domains_logged]
+    pub fn undo_update(&mut self, pos: usize, changes: &[(usize, u16)]) {
+        for &(p, removed) in changes {
+            self.domains[p] |= removed;
         }
+        self.empty_positions.insert(pos);
+    }
+
+    /// Positions that can not have the same value as `pos` with the rules
+    /// of the variant
+    fn peers(&self, pos: usize) -> impl Iterator<Item = usize> {
+        adjacent_positions(pos).chain(self.variant.extra_peers(pos))
+    }
+
+    /// Removes `value` from the possible values of `pos`
+    pub fn remove(&mut self, pos: usize, value: u8) {
+        self.domains[pos] &= !value_bit(value);
+    }
+
+    /// Checks if `value` is possible in `pos`
+    pub fn contains(&self, pos: usize, value: u8) -> bool {
+        self.domains[pos] & value_bit(value) != 0
+    }
 
-        self.empty_positions.remove(&pos);
+    /// Returns the possible values of `pos`, in ascending order
+    pub fn values(&self, pos: usize) -> impl Iterator<Item = u8> {
+        let domain = self.domains[pos];
+        (1..=N2 as u8).filter(move |&value| domain & value_bit(value) != 0)
     }
 }
 
@@ -77,21 +168,216 @@ impl Domains {
 struct SolveParams {
     max_solutions: usize,
     save_solutions: bool,
+    save_first: bool,
+    max_nodes: usize,
+    max_depth: usize,
+}
+
+impl Default for SolveParams {
+    fn default() -> Self {
+        Self {
+            max_solutions: 1,
+            save_solutions: false,
+            save_first: false,
+            max_nodes: usize::MAX,
+            max_depth: SIZE,
+        }
+    }
 }
 
 struct SolveResults {
     solution_count: usize,
     solutions: Option<Vec<SudokuBoard>>,
+    first_solution: Option<SudokuBoard>,
+    stats: SolveStats,
+    nodes_exhausted: bool,
+    depth_exceeded: bool,
+}
+
+/// Effort of the backtracking solver to solve a board, see
+/// [SudokuBoard::solve_with_stats]. Unlike [SudokuBoard::grade], it measures
+/// the work of the algorithm, not of a human.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Values tried in positions with more than one possible value
+    pub guesses: usize,
+    /// Values that led to a dead end and were undone
+    pub backtracks: usize,
+    /// Maximum number of positions filled by the search at the same time
+    pub max_depth: usize,
+    /// States of the board explored
+    pub nodes_visited: usize,
+}
+
+/// Rules of a sudoku, see [SudokuBoard::solve_variant].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    /// Rows, columns and groups can not repeat values
+    Classic,
+    /// Also known as X-Sudoku, the two main diagonals can not repeat values
+    /// either
+    Diagonal,
+    /// Two positions a knight's move away (like in chess) can not have the
+    /// same value
+    AntiKnight,
+    /// Two positions a king's move away, touching even diagonally, can not
+    /// have the same value
+    AntiKing,
+    /// Two orthogonally adjacent positions can not have consecutive values,
+    /// like 4 and 5
+    NonConsecutive,
+}
+
+impl Variant {
+    /// Positions that can not have the same value as `pos` because of this
+    /// variant, without the ones already sharing a row, column or group.
+    fn extra_peers(self, pos: usize) -> impl Iterator<Item = usize> {
+        let only = move |variant: Variant| Some(pos).filter(|_| self == variant).into_iter();
+        only(Variant::Diagonal)
+            .flat_map(diagonal_positions)
+            .chain(only(Variant::AntiKnight).flat_map(knight_positions))
+            .chain(only(Variant::AntiKing).flat_map(king_positions))
+            .filter(move |&p| !is_peer(pos, p))
+    }
+
+    /// Positions that can not have a value consecutive to the one of `pos`
+    /// because of this variant.
+    fn consecutive_peers(self, pos: usize) -> impl Iterator<Item = usize> {
+        Some(pos)
+            .filter(|_| self == Variant::NonConsecutive)
+            .into_iter()
+            .flat_map(orthogonal_positions)
+    }
+}
+
+/// Configuration of the backtracking solver, see
+/// [SudokuBoard::solve_with_config].
+#[derive(Clone, Debug)]
+pub struct SolverConfig {
+    /// Maximum depth of the recursion of the solver. Each level fills one
+    /// position, so [SIZE], the default, is never exceeded.
+    pub max_depth: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self { max_depth: SIZE }
+    }
+}
+
+/// Outcome of [SudokuBoard::solve_with_config]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// The board was solved
+    Solved,
+    /// The board has no solution
+    Unsolvable,
+    /// The search was stopped because it reached [SolverConfig::max_depth]
+    DepthLimitExceeded,
+}
+
+/// Reason why a board has no solution, see [SudokuBoard::explain_unsolvable].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unsolvable {
+    /// Two positions of the same row, column or group have the same value
+    Conflict(usize, usize),
+    /// An empty position where no value fits with the values of the board
+    NoCandidates(usize),
+    /// Every position has some value that fits, but filling them always
+    /// leads to a contradiction
+    NoSolution,
+}
+
+/// Position being tried by the backtracking, with its possible values and
+/// the changes in the domains made by the value being tried.
+struct Frame {
+    pos: usize,
+    possible: Vec<u8>,
+    next: usize,
+    changes: Option<Vec<(usize, u16)>>,
+}
+
+/// Lazy iterator over the solutions of a board, created with
+/// [SudokuBoard::solutions]. The search is resumed from where it stopped
+/// each time a solution is asked.
+pub struct Solutions {
+    board: SudokuBoard,
+    domains: Domains,
+    stack: Vec<Frame>,
+    // if the next step is going down to a new node of the search, instead
+    // of back up to try another value
+    descend: bool,
+}
+
+impl Iterator for Solutions {
+    type Item = SudokuBoard;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.descend {
+                self.descend = false;
+                match self.board.get_empty_position(&self.domains, SIZE / 2) {
+                    Some(pos) => self.stack.push(Frame {
+                        pos,
+                        possible: self.board.get_possible(pos, &self.domains, N),
+                        next: 0,
+                        changes: None,
+                    }),
+                    None => return Some(self.board.clone()),
+                }
+            }
+
+            // go back up until a frame has another value to try, the same
+            // as the backtracking
+            let frame = self.stack.last_mut()?;
+            if let Some(changes) = frame.changes.take() {
+                self.domains.undo_update(frame.pos, &changes);
+            }
+            while let Some(&n) = frame.possible.get(frame.next) {
+                frame.next += 1;
+                if !self.board.is_valid(frame.pos, n) {
+                    continue;
+                }
+                self.board.0[frame.pos] = n;
+                let changes = self.domains.update_domains_logged(frame.pos, n);
+                if self.board.still_possible(&self.domains)
+                    && self.domains.cages_possible(&self.board, frame.pos)
+                {
+                    frame.changes = Some(changes);
+                    self.descend = true;
+                    break;
+                }
+                self.domains.undo_update(frame.pos, &changes);
+            }
+            if !self.descend {
+                self.board.0[frame.pos] = 0;
+                self.stack.pop();
+            }
+        }
+    }
 }
 
 // multiple (and similar) backtracking functions to avoid checking parameters
 // to make them behave differently
 impl SudokuBoard {
     fn backtracking(&mut self, params: SolveParams) -> SolveResults {
+        let domains = Domains::calculate_domains(self);
+        self.backtracking_with_domains(domains, params)
+    }
+
+    fn backtracking_with_domains(
+        &mut self,
+        mut domains: Domains,
+        params: SolveParams,
+    ) -> SolveResults {
         let mut metadata = {
             let mut md = SolveResults {
                 solution_count: 0,
                 solutions: None,
+                first_solution: None,
+                stats: SolveStats::default(),
+                nodes_exhausted: false,
+                depth_exceeded: false,
             };
             if params.save_solutions {
                 md.solutions = Some(Vec::new());
@@ -99,102 +385,441 @@ impl SudokuBoard {
             md
         };
 
-        let mut domains = Domains::calculate_domains(self);
-
-        self.backtracking_rec(&mut domains, &params, &mut metadata);
+        if params.max_solutions > 0 {
+            self.backtracking_iter(&mut domains, &params, &mut metadata);
+        }
         metadata
     }
 
-    fn backtracking_rec(
+    fn backtracking_iter(
         &mut self,
         domains: &mut Domains,
         params: &SolveParams,
         md: &mut SolveResults,
     ) {
-        // get the first empty postion
-        let pos = self.get_empty_position(domains, SIZE / 2);
-        if pos.is_none() {
-            // if there's none, we found a solution
-            md.solution_count += 1;
-            if params.save_solutions {
-                md.solutions.as_mut().unwrap().push(self.clone());
+        // each frame is an empty position being tried, the path from the
+        // first one to the current state of the search
+        let mut stack: Vec<Frame> = Vec::new();
+
+        'node: loop {
+            // a new node of the search, one level below the top of the stack
+            if md.stats.nodes_visited >= params.max_nodes {
+                md.nodes_exhausted = true;
+            } else if stack.len() > params.max_depth {
+                md.depth_exceeded = true;
+            } else {
+                md.stats.nodes_visited += 1;
+                // get the first empty postion
+                match self.get_empty_position(domains, SIZE / 2) {
+                    Some(pos) => {
+                        stack.push(Frame {
+                            pos,
+                            possible: self.get_possible(pos, domains, N),
+                            next: 0,
+                            changes: None,
+                        });
+                        md.stats.max_depth = md.stats.max_depth.max(stack.len());
+                    }
+                    None => {
+                        // if there's none, we found a solution
+                        md.solution_count += 1;
+                        if params.save_solutions {
+                            md.solutions.as_mut().unwrap().push(self.clone());
+                        }
+                        if params.save_first && md.first_solution.is_none() {
+                            md.first_solution = Some(self.clone());
+                        }
+                    }
+                }
             }
-            return;
-        }
-        let pos = pos.unwrap();
-        let mut temp_domains: Domains;
-
-        // try all possible values
-        for n in self.get_possible(pos, domains, N) {
-            // if the value can be fitted (maybe this check is unnecesary
-            // because of get_possible and the domain calculations)
-            if self.is_valid(pos, n) {
-                // apply the value and update the domains
-                self.0[pos] = n;
-                temp_domains = domains.clone();
-                domains.update_domains(pos, n);
-                // if sudoku can still be solved
-                if self.still_possible(domains) {
-                    // continue searching
-                    self.backtracking_rec(domains, params, md);
+
+            // go back up until a frame has another value to try
+            while let Some(frame) = stack.last_mut() {
+                if let Some(changes) = frame.changes.take() {
+                    // backtrack: restore the domains
+                    domains.undo_update(frame.pos, &changes);
+                    if md.solution_count >= params.max_solutions
+                        || md.nodes_exhausted
+                        || md.depth_exceeded
+                    {
+                        return;
+                    }
+                    md.stats.backtracks += 1;
                 }
-                // backtrack: restore the position and the domains
-                *domains = temp_domains;
 
-                if md.solution_count >= params.max_solutions {
-                    return;
+                // try the remaining possible values
+                while let Some(&n) = frame.possible.get(frame.next) {
+                    frame.next += 1;
+                    // if the value can be fitted (maybe this check is unnecesary
+                    // because of get_possible and the domain calculations)
+                    if !self.is_valid(frame.pos, n) {
+                        continue;
+                    }
+                    // apply the value and update the domains
+                    if frame.possible.len() > 1 {
+                        md.stats.guesses += 1;
+                    }
+                    self.0[frame.pos] = n;
+                    let changes = domains.update_domains_logged(frame.pos, n);
+                    // if sudoku can still be solved, continue searching
+                    if self.still_possible(domains) && domains.cages_possible(self, frame.pos) {
+                        frame.changes = Some(changes);
+                        continue 'node;
+                    }
+                    domains.undo_update(frame.pos, &changes);
+                    md.stats.backtracks += 1;
                 }
+
+                self.0[frame.pos] = 0;
+                stack.pop();
             }
+            return;
         }
-        self.0[pos] = 0;
     }
 
     /// Solves the sudoku in place, returns true if the sudoku could be solved.
     /// Gets the first solution, does not check for more.
     pub fn solve(&mut self) -> bool {
-        self.backtracking(SolveParams {
-            max_solutions: 1,
-            save_solutions: false,
-        })
-        .solution_count
+        self.backtracking(SolveParams::default()).solution_count > 0
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], and returns the
+    /// positions it filled with their values, in increasing order of
+    /// position. Emptying them restores the board, so the solve can be undone
+    /// without keeping a copy. `None` if the sudoku could not be solved, then
+    /// the board is unchanged.
+    pub fn solve_recording(&mut self) -> Option<Vec<(usize, u8)>> {
+        let empty: Vec<usize> = (0..SIZE).filter(|&pos| self.0[pos] == 0).collect();
+        if !self.solve() {
+            return None;
+        }
+        Some(empty.into_iter().map(|pos| (pos, self.0[pos])).collect())
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], with the rules of
+    /// `variant`. Returns false if it has no solution, also if the givens
+    /// already break the rules of the variant.
+    pub fn solve_variant(&mut self, variant: Variant) -> bool {
+        let repeated = (0..SIZE).any(|pos| {
+            self.0[pos] != 0 && variant.extra_peers(pos).any(|p| self.0[p] == self.0[pos])
+        });
+        let consecutive = (0..SIZE).any(|pos| {
+            variant
+                .consecutive_peers(pos)
+                .any(|p| are_consecutive(self.0[p], self.0[pos]))
+        });
+        if repeated || consecutive {
+            return false;
+        }
+        let domains = Domains::calculate_variant_domains(self, variant);
+        self.solve_from_domains(domains)
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], starting from
+    /// `domains`, that may have more rules than the classic ones.
+    pub(crate) fn solve_from_domains(&mut self, domains: Domains) -> bool {
+        self.backtracking_with_domains(domains, SolveParams::default())
+            .solution_count
+            > 0
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], and also returns
+    /// the effort of the search.
+    pub fn solve_with_stats(&mut self) -> (bool, SolveStats) {
+        let results = self.backtracking(SolveParams::default());
+        (results.solution_count > 0, results.stats)
+    }
+
+    /// Solves a copy of the sudoku ignoring the fewest conflicting givens
+    /// (see [SudokuBoard::find_conflicts]) needed to solve the rest. Useful
+    /// for boards with typos, like the ones read from a photo.
+    ///
+    /// Returns the solution and the ignored positions. If ignoring all of the
+    /// conflicting givens is not enough, the board is returned without them
+    /// and not solved.
+    pub fn solve_best_effort(&self) -> (SudokuBoard, Vec<usize>) {
+        let conflicts = self.find_conflicts();
+
+        for size in 0..=conflicts.len() {
+            let mut ignored: Vec<usize> = (0..size).collect();
+            loop {
+                let mut board = self.clone();
+                for &i in &ignored {
+                    board.0[conflicts[i]] = 0;
+                }
+                if board.find_conflicts().is_empty() && board.solve() {
+                    return (board, ignored.iter().map(|&i| conflicts[i]).collect());
+                }
+                if !next_combination(&mut ignored, conflicts.len()) {
+                    break;
+                }
+            }
+        }
+
+        let mut board = self.clone();
+        for &pos in &conflicts {
+            board.0[pos] = 0;
+        }
+        (board, conflicts)
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], but trying the
+    /// possible values of each position in a random order given by `seed`.
+    /// The same board and seed always get the same solution, which can be
+    /// any of the solutions of the board.
+    pub fn solve_seeded(&mut self, seed: &str) -> bool {
+        let mut rng: Pcg64 = Seeder::from(seed).make_rng();
+        let mut domains = Domains::calculate_domains(self);
+        self.backtracking_random_rec(&mut domains, &mut rng)
+    }
+
+    fn backtracking_random_rec(&mut self, domains: &mut Domains, rng: &mut Pcg64) -> bool {
+        let pos = match self.get_empty_position(domains, SIZE / 2) {
+            Some(pos) => pos,
+            None => return true,
+        };
+
+        let mut possible = self.get_possible(pos, domains, usize::MAX);
+        possible.shuffle(rng);
+        for n in possible {
+            self.0[pos] = n;
+            let temp_domains = domains.clone();
+            domains.update_domains(pos, n);
+            if self.still_possible(domains) && self.backtracking_random_rec(domains, rng) {
+                return true;
+            }
+            *domains = temp_domains;
+        }
+        self.0[pos] = 0;
+        false
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], with the limits of
+    /// `config`. If it is not [SolveOutcome::Solved], the board is unchanged.
+    pub fn solve_with_config(&mut self, config: &SolverConfig) -> SolveOutcome {
+        let mut board = self.clone();
+        let results = board.backtracking(SolveParams {
+            max_depth: config.max_depth,
+            ..SolveParams::default()
+        });
+        if results.solution_count > 0 {
+            *self = board;
+            SolveOutcome::Solved
+        } else if results.depth_exceeded {
+            SolveOutcome::DepthLimitExceeded
+        } else {
+            SolveOutcome::Unsolvable
+        }
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], but first removes
+    /// the value `n` from the possible values of `pos` for each `(pos, n)` in
+    /// `exclusions`.
+    ///
+    /// Returns false if the sudoku could not be solved or if an exclusion
+    /// contradicts the board, i.e., it excludes the value already placed in
+    /// a cell, or it is out of range.
+    pub fn solve_with_exclusions(&mut self, exclusions: &[(usize, u8)]) -> bool {
+        let mut domains = Domains::calculate_domains(self);
+
+        for &(pos, n) in exclusions {
+            if pos >= SIZE || n == 0 || n > N2 as u8 || self.0[pos] == n {
+                return false;
+            }
+            domains.domains[pos] &= !value_bit(n);
+        }
+
+        self.backtracking_with_domains(domains, SolveParams::default())
+            .solution_count
+            > 0
+    }
+
+    /// Solves the sudoku in place like [SudokuBoard::solve], but each empty
+    /// position can only take the values marked in `candidates`, where
+    /// `candidates[pos][n - 1]` allows the value `n`. With an empty board, it
+    /// solves a pencilmark-only puzzle (Sukaku), see
+    /// [SudokuBoard::parse_sukaku].
+    ///
+    /// Returns false if the sudoku could not be solved, also if a value
+    /// already placed is not one of the candidates of its position.
+    pub fn solve_with_candidates(&mut self, candidates: &[[bool; N2]; SIZE]) -> bool {
+        let mut domains = Domains::calculate_domains(self);
+
+        for (pos, allowed) in candidates.iter().enumerate() {
+            let mask = (1..=N2 as u8)
+                .filter(|&n| allowed[n as usize - 1])
+                .fold(0, |mask, n| mask | value_bit(n));
+            let value = self.0[pos];
+            if value != 0 && mask & value_bit(value) == 0 {
+                return false;
+            }
+            domains.domains[pos] &= mask;
+        }
+
+        self.backtracking_with_domains(domains, SolveParams::default())
+            .solution_count
             > 0
     }
 
+    /// Parses the candidates of a pencilmark-only puzzle (Sukaku) for
+    /// [SudokuBoard::solve_with_candidates]. The input has N2 characters for
+    /// each position in reading order, where the `n`-th one is the digit `n`
+    /// if it is a candidate, or a `.` or a `0` if not, so 729 characters for
+    /// a 9x9 board.
+    ///
+    /// Fails with [ParseError::WrongLength] or, if any other character is
+    /// found, like a digit in the place of another one, with
+    /// [ParseError::InvalidCharacter].
+    pub fn parse_sukaku(input: &str) -> Result<[[bool; N2]; SIZE], ParseError> {
+        let input = input.trim();
+        let len = input.chars().count();
+        if len != SIZE * N2 {
+            return Err(ParseError::WrongLength {
+                expected: SIZE * N2,
+                got: len,
+            });
+        }
+        let mut candidates = [[false; N2]; SIZE];
+        for (i, ch) in input.chars().enumerate() {
+            let n = i % N2 + 1;
+            candidates[i / N2][n - 1] = match symbol_value(ch, N2) {
+                Some(0) => false,
+                Some(value) if value as usize == n => true,
+                _ => return Err(ParseError::InvalidCharacter { pos: i, ch }),
+            };
+        }
+        Ok(candidates)
+    }
+
     /// Solves the sudoku finding at most `max` solutions.
     pub fn solve_all(&self, max: usize) -> Vec<SudokuBoard> {
         self.clone()
             .backtracking(SolveParams {
                 max_solutions: max,
                 save_solutions: true,
+                ..SolveParams::default()
             })
             .solutions
             .unwrap()
     }
 
+    /// Returns a lazy iterator over all the solutions of the sudoku, in the
+    /// same order as [SudokuBoard::solve_all]. Each solution is searched when
+    /// it is asked for, so only the current path of the search is kept in
+    /// memory, like `board.solutions().take(5)` to get the first 5.
+    pub fn solutions(&self) -> Solutions {
+        Solutions {
+            board: self.clone(),
+            domains: Domains::calculate_domains(self),
+            stack: Vec::new(),
+            descend: true,
+        }
+    }
+
+    /// Solves the sudoku finding at most `max` solutions like
+    /// [SudokuBoard::solve_all], exploring the values of the first position
+    /// it would branch on in parallel. The solutions are the same and in the
+    /// same order as [SudokuBoard::solve_all].
+    ///
+    /// Every branch stops at `max` solutions, so it is faster when the
+    /// solutions are spread over the branches, like in almost empty boards.
+    #[cfg(feature = "rayon")]
+    pub fn solve_all_parallel(&self, max: usize) -> Vec<SudokuBoard> {
+        use rayon::prelude::*;
+
+        let domains = Domains::calculate_domains(self);
+        let pos = match self.get_empty_position(&domains, SIZE / 2) {
+            Some(pos) => pos,
+            None => return self.solve_all(max),
+        };
+
+        let branches: Vec<Vec<SudokuBoard>> = self
+            .get_possible(pos, &domains, N)
+            .into_par_iter()
+            .filter(|&n| self.is_valid(pos, n))
+            .map(|n| {
+                let mut board = self.clone();
+                board.0[pos] = n;
+                board.solve_all(max)
+            })
+            .collect();
+
+        let mut solutions: Vec<SudokuBoard> = branches.into_iter().flatten().collect();
+        solutions.truncate(max);
+        solutions
+    }
+
+    /// Solves the sudoku finding at most `max` solutions like
+    /// [SudokuBoard::solve_all], and also returns the positions that do not
+    /// have the same value in all of them, in increasing order.
+    ///
+    /// With a unique solution, the positions are empty.
+    pub fn solution_differences(&self, max: usize) -> (Vec<SudokuBoard>, Vec<usize>) {
+        let solutions = self.solve_all(max);
+        let differences = match solutions.split_first() {
+            Some((first, rest)) => (0..SIZE)
+                .filter(|&pos| rest.iter().any(|s| s.0[pos] != first.0[pos]))
+                .collect(),
+            None => Vec::new(),
+        };
+        (solutions, differences)
+    }
+
     /// Counts the number of solutions of the sudoku.
     /// It stops counting when `max` is reached.
     pub fn count_solutions(&self, max: usize) -> usize {
         self.clone()
             .backtracking(SolveParams {
                 max_solutions: max,
-                save_solutions: false,
+                ..SolveParams::default()
             })
             .solution_count
     }
 
+    /// Solves the sudoku and counts its solutions in the same search, like
+    /// [SudokuBoard::solve] and [SudokuBoard::count_solutions] but only
+    /// searching once. Returns the first solution found, `None` if there is
+    /// none, and the number of solutions, at most `max`.
+    ///
+    /// `solve_and_count(2)` solves a board and tells if the solution is
+    /// unique.
+    pub fn solve_and_count(&self, max: usize) -> (Option<SudokuBoard>, usize) {
+        let results = self.clone().backtracking(SolveParams {
+            max_solutions: max,
+            save_first: true,
+            ..SolveParams::default()
+        });
+        (results.first_solution, results.solution_count)
+    }
+
+    /// Checks if the sudoku has exactly one solution. It stops searching as
+    /// soon as a second one is found.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// Counts the number of solutions of the sudoku like
+    /// [SudokuBoard::count_solutions], but it also stops after exploring
+    /// `max_nodes` states of the search. Useful to limit the work done on
+    /// boards given by users, like almost empty ones.
+    ///
+    /// Returns the number of solutions found and if the search stopped because
+    /// `max_nodes` was reached, so the count may be incomplete.
+    pub fn count_solutions_bounded(&self, max_solutions: usize, max_nodes: usize) -> (usize, bool) {
+        let results = self.clone().backtracking(SolveParams {
+            max_solutions,
+            max_nodes,
+            ..SolveParams::default()
+        });
+        (results.solution_count, results.nodes_exhausted)
+    }
+
     fn get_empty_position(&self, domains: &Domains, min_tie_to_solve: usize) -> Option<usize> {
         // Calculate the number of available values for each empty position
         let mut values: Vec<(u32, usize)> = domains
             .empty_positions
             .iter()
-            .map(|&pos| {
-                (
-                    domains.domains[pos]
-                        .iter()
-                        .fold(0, |acc, &x| acc + if x { 1 } else { 0 }),
-                    pos,
-                )
-            })
+            .map(|pos| (domains.domains[pos].count_ones(), pos))
             .collect();
 
         values.sort_unstable();
@@ -222,22 +847,19 @@ impl SudokuBoard {
     }
 
     fn get_possible(&self, pos: usize, domains: &Domains, min_possible_ordered: usize) -> Vec<u8> {
-        let possible: Vec<_> = domains.domains[pos]
-            .iter()
-            .enumerate()
-            .filter(|(_, &possible)| possible)
-            .map(|(value, _)| value as u8 + 1)
-            .collect();
+        let possible: Vec<_> = domains.values(pos).collect();
 
         if possible.len() > min_possible_ordered {
             let mut values = domains
                 .empty_positions
                 .iter()
-                .map(|&pos| domains.domains[pos])
+                .map(|pos| domains.domains[pos])
                 .fold([0; N2], |mut acc, domain| {
-                    acc.iter_mut()
-                        .zip(domain.iter())
-                        .for_each(|(accref, x)| *accref += if *x { 0 } else { 1 });
+                    for (i, accref) in acc.iter_mut().enumerate() {
+                        if domain & (1 << i) == 0 {
+                            *accref += 1;
+                        }
+                    }
                     acc
                 })
                 .iter()
@@ -256,9 +878,109 @@ impl SudokuBoard {
         !domains
             .empty_positions
             .iter()
-            .map(|&pos| domains.domains[pos])
-            .map(|domain| domain.iter().fold(0, |acc, &x| acc + if x { 1 } else { 0 }))
-            .any(|sum| sum == 0)
+            .any(|pos| domains.domains[pos] == 0)
+    }
+
+    /// Checks that no row, column or group has a repeated value. A board
+    /// that is not consistent has no solution.
+    pub fn is_consistent(&self) -> bool {
+        (0..SIZE).all(|pos| {
+            let value = self.0[pos];
+            value == 0 || adjacent_positions(pos).all(|p| self.0[p] != value)
+        })
+    }
+
+    /// Returns the pairs of positions that share a row, column or group and
+    /// have the same value. The first position of each pair is the lowest,
+    /// and the pairs are sorted.
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let mut conflicts = Vec::new();
+        for pos in (0..SIZE).filter(|&pos| self.0[pos] != 0) {
+            let mut peers: Vec<usize> = adjacent_positions(pos)
+                .filter(|&p| p > pos && self.0[p] == self.0[pos])
+                .collect();
+            peers.sort_unstable();
+            conflicts.extend(peers.into_iter().map(|p| (pos, p)));
+        }
+        conflicts
+    }
+
+    /// Explains why the board has no solution, `None` if it has one. The
+    /// simplest reason is given: first a [Unsolvable::Conflict] between
+    /// values, then an empty position without candidates, and only then
+    /// [Unsolvable::NoSolution] after searching for one.
+    pub fn explain_unsolvable(&self) -> Option<Unsolvable> {
+        if let Some(&(a, b)) = self.conflicts().first() {
+            return Some(Unsolvable::Conflict(a, b));
+        }
+        let domains = Domains::calculate_domains(self);
+        if let Some(pos) = (0..SIZE).find(|&pos| self.0[pos] == 0 && domains.domains[pos] == 0) {
+            return Some(Unsolvable::NoCandidates(pos));
+        }
+        if self.count_solutions(1) == 0 {
+            Some(Unsolvable::NoSolution)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the positions with a value that is repeated in their row,
+    /// column or group, in increasing order.
+    pub fn find_conflicts(&self) -> Vec<usize> {
+        (0..SIZE)
+            .filter(|&pos| {
+                let value = self.0[pos];
+                value != 0 && adjacent_positions(pos).any(|p| self.0[p] == value)
+            })
+            .collect()
+    }
+
+    /// Checks if the board is complete and every row, column and group has
+    /// all the values once, i.e., it is a valid solution. Unlike
+    /// [SudokuBoard::is_consistent], a board with empty positions is not.
+    pub fn is_solved(&self) -> bool {
+        (0..N2).all(|i| {
+            self.has_all_values(row_positions(i))
+                && self.has_all_values(col_positions(i))
+                && self.has_all_values(group_positions_by_index(i))
+        })
+    }
+
+    fn has_all_values(&self, positions: impl Iterator<Item = usize>) -> bool {
+        let mut seen = 0;
+        for pos in positions {
+            match self.0[pos] {
+                0 => return false,
+                value if value as usize > N2 => return false,
+                value => seen |= value_bit(value),
+            }
+        }
+        seen == ALL_VALUES
+    }
+
+    /// Panics if the board is not a complete and valid solution, with a
+    /// message pointing to the first unit with an empty position or a
+    /// repeated value. Useful in tests.
+    pub fn assert_valid_solution(&self) {
+        for (unit, (values, kind)) in self.units().enumerate() {
+            let index = unit % N2;
+            let mut seen = [false; N2];
+            for &value in values.iter() {
+                if value == 0 || value > N2 as u8 {
+                    panic!(
+                        "{:?} {} has an empty or invalid position: {:?}",
+                        kind, index, values
+                    );
+                }
+                if seen[value as usize - 1] {
+                    panic!(
+                        "{:?} {} has the value {} repeated: {:?}",
+                        kind, index, value, values
+                    );
+                }
+                seen[value as usize - 1] = true;
+            }
+        }
     }
 
     /// Checks if `n` can be placed at `pos`. It does not check if that will
@@ -272,6 +994,15 @@ impl SudokuBoard {
         true
     }
 
+    /// Like [SudokuBoard::is_valid], also checking the rules of `variant`.
+    pub fn is_valid_variant(&self, pos: usize, n: u8, variant: Variant) -> bool {
+        self.is_valid(pos, n)
+            && variant.extra_peers(pos).all(|p| self.0[p] != n)
+            && !variant
+                .consecutive_peers(pos)
+                .any(|p| are_consecutive(self.0[p], n))
+    }
+
     /// Checks if `n` can be placed in the row `row`.
     pub fn is_valid_row(&self, row: usize, n: u8) -> bool {
         for p in row_positions(row) {
@@ -304,35 +1035,92 @@ impl SudokuBoard {
     }
 }
 
+/// Advances `indices`, sorted indices in `0..n`, to the next combination
+/// of the same size in lexicographic order. False if it was the last one.
+fn next_combination(indices: &mut [usize], n: usize) -> bool {
+    let k = indices.len();
+    for i in (0..k).rev() {
+        if indices[i] < n - k + i {
+            indices[i] += 1;
+            for j in i + 1..k {
+                indices[j] = indices[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
 // Generate
 impl SudokuBoard {
     /// Generates a solved board from a seed.
-    pub fn generate_from_seed<T: std::hash::Hash>(seed: &T) -> Self {
+    ///
+    /// The same seed generates the same board on every platform, as long as
+    /// its hash does not depend on it. Strings are safe, but `usize` is not
+    /// because its size changes.
+    pub fn generate_from_seed<T: core::hash::Hash>(seed: &T) -> Self {
         let mut rng = Seeder::from(seed).make_rng();
         Self::generate(&mut rng)
     }
 
     /// Generates a solved board using a PRNG.
     pub fn generate(rng: &mut Pcg64) -> Self {
-        // loop while the board is not solved
-        let mut solution = Self::default();
+        Self::generate_with(rng)
+    }
 
-        // fill the groups in the main diagonal
+    /// Generates a solved board using any random number generator.
+    ///
+    /// The same board is generated for the same state of `rng`.
+    pub fn generate_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::generate_inner(rng, None)
+    }
+
+    /// Generates a solved board like [SudokuBoard::generate], but with a
+    /// given number of random substitutions instead of a random number
+    /// between 10 and 20.
+    ///
+    /// The groups in the main diagonal are always random, and after the
+    /// substitutions, the rest of the board is filled by the solver, which
+    /// always makes the same choices. So with few substitutions, boards share
+    /// more of their structure, and with more the generated boards are spread
+    /// more evenly, at the cost of a slower generation. The substitutions are
+    /// limited by the empty positions left by the diagonal groups.
+    pub fn generate_with_diversity(rng: &mut Pcg64, substitutions: usize) -> Self {
+        Self::generate_inner(rng, Some(substitutions))
+    }
+
+    /// Returns a board where only the groups in the main diagonal are filled
+    /// randomly, and the rest is empty. It is the first step of
+    /// [SudokuBoard::generate]: those groups do not share any row or column,
+    /// so the board is always valid and it can always be solved.
+    pub fn with_diagonal_boxes(rng: &mut Pcg64) -> Self {
+        Self::diagonal_boxes(rng)
+    }
+
+    fn diagonal_boxes<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut board = Self::default();
         for i in 0..N {
             let mut numbers = (1..=N2 as u8).collect::<Vec<u8>>();
             numbers.shuffle(rng);
 
             for (p, val) in group_positions(i * N, i * N).zip(numbers) {
-                solution.0[p] = val;
+                board.0[p] = val;
             }
         }
+        board
+    }
+
+    fn generate_inner<R: Rng + ?Sized>(rng: &mut R, substitutions: Option<usize>) -> Self {
+        // fill the groups in the main diagonal
+        let mut solution = Self::diagonal_boxes(rng);
 
         let mut domains = Domains::calculate_domains(&solution);
 
         // change some random positions to increase randomness
-        let sustitutions: u32 = rng.gen_range(10u32..20u32);
-        let mut empty_positions: Vec<usize> = domains.empty_positions.iter().cloned().collect();
+        let sustitutions = substitutions.unwrap_or_else(|| rng.gen_range(10u32..20u32) as usize);
+        let mut empty_positions: Vec<usize> = domains.empty_positions.iter().collect();
         empty_positions.sort_unstable();
+        let sustitutions = sustitutions.min(empty_positions.len());
         for _ in 0..sustitutions {
             // The casting from usize to u32 is to make the seed generation stable between
             // platforms where usize has different sizes.
@@ -357,252 +1145,2794 @@ impl SudokuBoard {
     }
 }
 
-// Interface
+// Analysis
 impl SudokuBoard {
-    /// Returns the 1 line representation of the board.
-    /// Scanning row by row. A dot means an empty position.
-    pub fn to_line_string(&self) -> String {
-        self.0
-            .iter()
-            .map(|&x| match x {
-                0 => ".".to_string(),
-                x => x.to_string(),
-            })
-            .collect()
-    }
-}
+    /// Measures the information given by the clues of the board, in bits.
+    ///
+    /// After propagating the clues to the rest of the board, each cell with
+    /// `d` remaining possible values has an uncertainty of `log2(d)` bits,
+    /// while a cell with no constraint at all has `log2(9)`. The result is:
+    ///
+    /// ```text
+    /// entropy = sum over every cell of (log2(9) - log2(d))
+    /// ```
+    ///
+    /// where `d = 1` for given cells. An empty board is `0.0` and a complete
+    /// board is `81 * log2(9)`. Empty cells without any possible value (a
+    /// contradiction) do not give any information, so they count as `0`.
+    ///
+    /// Only with the `std` feature, for the logarithms.
+    #[cfg(feature = "std")]
+    pub fn clue_entropy(&self) -> f64 {
+        let domains = Domains::calculate_domains(self);
+        let max_bits = (N2 as f64).log2();
+
+        (0..SIZE)
+            .map(|pos| {
+                if self.0[pos] != 0 {
+                    return max_bits;
+                }
+                match domains.domains[pos].count_ones() {
+                    0 => 0.0,
+                    possible => max_bits - (possible as f64).log2(),
+                }
+            })
+            .sum()
+    }
+
+    /// Checks if the board has a unique solution and removing any of its
+    /// values would make it have more than one, so no value is redundant.
+    pub fn is_minimal(&self) -> bool {
+        if !self.has_unique_solution() {
+            return false;
+        }
+        let mut board = self.clone();
+        (0..SIZE).filter(|&pos| self.0[pos] != 0).all(|pos| {
+            board.0[pos] = 0;
+            let redundant = board.has_unique_solution();
+            board.0[pos] = self.0[pos];
+            !redundant
+        })
+    }
+
+    /// Returns the number of given positions, the ones with a value.
+    pub fn clue_count(&self) -> usize {
+        self.0.iter().filter(|&&value| value != 0).count()
+    }
+
+    /// Returns the number of empty positions, `SIZE - clue_count()`.
+    pub fn empty_count(&self) -> usize {
+        SIZE - self.clue_count()
+    }
+
+    /// Returns a mask where a position is true if it is given, i.e., it is
+    /// not empty.
+    pub fn given_mask(&self) -> [bool; SIZE] {
+        let mut mask = [false; SIZE];
+        for (m, &value) in mask.iter_mut().zip(self.0.iter()) {
+            *m = value != 0;
+        }
+        mask
+    }
+
+    /// Returns the negative space of the givens: a board where the given
+    /// positions are empty and the empty positions have the value of the
+    /// first solution found. If the board has no solution, the result is an
+    /// empty board.
+    pub fn clue_complement(&self) -> SudokuBoard {
+        // the solver does not check the givens, so conflicting ones would
+        // make it search the whole tree
+        let mut solution = self.clone();
+        if !self.find_conflicts().is_empty() || !solution.solve() {
+            return SudokuBoard::default();
+        }
+        for (value, &given) in solution.0.iter_mut().zip(self.0.iter()) {
+            if given != 0 {
+                *value = 0;
+            }
+        }
+        solution
+    }
+
+    /// Returns the values that can be placed in the empty position `pos`
+    /// without repeating one of its row, column or group, i.e., its pencil
+    /// marks. A position with a value has no candidates.
+    pub fn candidates(&self, pos: usize) -> Vec<u8> {
+        if self.0[pos] != 0 {
+            return Vec::new();
+        }
+        let mut possible = [true; N2];
+        for p in adjacent_positions(pos) {
+            if self.0[p] != 0 {
+                possible[(self.0[p] - 1) as usize] = false;
+            }
+        }
+        (1..=N2 as u8)
+            .filter(|&value| possible[(value - 1) as usize])
+            .collect()
+    }
+
+    /// Returns the candidates of every position as a bitmask, where bit `n`
+    /// is set if the value `n + 1` can be placed. Positions with a value are
+    /// `0`. Unlike [SudokuBoard::candidates], it does not allocate, so it can
+    /// be passed through FFI.
+    pub fn candidates_mask(&self) -> [u16; SIZE] {
+        let mut mask = Domains::calculate_domains(self).domains;
+        for (m, &value) in mask.iter_mut().zip(self.0.iter()) {
+            if value != 0 {
+                *m = 0;
+            }
+        }
+        mask
+    }
+
+    /// Returns, for each given position in order, the number of empty
+    /// positions that it constrains directly, i.e., its empty peers. Clues
+    /// with a low impact are the best candidates to be removed.
+    pub fn clue_impact(&self) -> Vec<(usize, usize)> {
+        (0..SIZE)
+            .filter(|&pos| self.0[pos] != 0)
+            .map(|pos| {
+                let empty_peers = adjacent_positions(pos).filter(|&p| self.0[p] == 0).count();
+                (pos, empty_peers)
+            })
+            .collect()
+    }
+
+    /// Returns true if the positions `a` and `b` share a row, a column or
+    /// a group, so they can never hold the same value.
+    pub fn is_peer(a: usize, b: usize) -> bool {
+        is_peer(a, b)
+    }
+
+    /// Checks if the cells at `a` and `b` could hold the same value. It is
+    /// false when they are peers or when their possible values, given the
+    /// current state of the board, do not have any value in common.
+    pub fn can_share_value(&self, a: usize, b: usize) -> bool {
+        if a == b {
+            return true;
+        }
+        if is_peer(a, b) {
+            return false;
+        }
+        let domains = Domains::calculate_domains(self);
+        let possible = |pos: usize| match self.0[pos] {
+            0 => domains.domains[pos],
+            n => value_bit(n),
+        };
+        possible(a) & possible(b) != 0
+    }
+
+    /// Checks if the pattern of given positions has the symmetry, i.e., every
+    /// position is given if and only if its mirrored position is given. The
+    /// values are not taken into account. [Symmetry::None] is always true.
+    pub fn has_symmetry(&self, symmetry: Symmetry) -> bool {
+        (0..SIZE).all(|pos| (self.0[pos] != 0) == (self.0[symmetry.mirror(pos)] != 0))
+    }
+
+    /// Measures how close the pattern of given positions is to the
+    /// [Symmetry::Rotational180]: the fraction of givens whose rotated
+    /// position is also given. `1.0` is a perfectly symmetric pattern, like
+    /// the one of a board without givens.
+    pub fn symmetry_score(&self) -> f64 {
+        let givens = self.clue_count();
+        if givens == 0 {
+            return 1.0;
+        }
+        let symmetric = (0..SIZE)
+            .filter(|&pos| self.0[pos] != 0 && self.0[Symmetry::Rotational180.mirror(pos)] != 0)
+            .count();
+        symmetric as f64 / givens as f64
+    }
+
+    /// Returns all the symmetries of the pattern of given positions, see
+    /// [SudokuBoard::has_symmetry]. An empty vector means that the pattern is
+    /// asymmetric.
+    pub fn detect_symmetry(&self) -> Vec<Symmetry> {
+        Symmetry::all()
+            .iter()
+            .copied()
+            .filter(|&symmetry| symmetry != Symmetry::None && self.has_symmetry(symmetry))
+            .collect()
+    }
+
+    /// Returns a new board with the value of each position moved to the
+    /// position given by `to(row, col)`.
+    fn moved(&self, to: impl Fn(usize, usize) -> (usize, usize)) -> SudokuBoard {
+        let mut board = SudokuBoard::default();
+        for (pos, &value) in self.0.iter().enumerate() {
+            let (row, col) = to_row_col(pos);
+            let (row, col) = to(row, col);
+            board.0[to_pos(row, col)] = value;
+        }
+        board
+    }
+
+    /// Returns the board rotated 90 degrees clockwise.
+    pub fn rotate_90(&self) -> SudokuBoard {
+        self.moved(|row, col| (col, N2 - 1 - row))
+    }
+
+    /// Returns the board mirrored over the middle row, like
+    /// [Symmetry::Horizontal].
+    pub fn reflect_horizontal(&self) -> SudokuBoard {
+        self.moved(|row, col| (N2 - 1 - row, col))
+    }
+
+    /// Returns the board mirrored over the main diagonal, so the rows become
+    /// the columns.
+    pub fn transpose(&self) -> SudokuBoard {
+        self.moved(|row, col| (col, row))
+    }
+
+    /// Returns the board with every value `n` replaced by `mapping[n - 1]`.
+    /// Empty positions stay empty.
+    ///
+    /// Like the other transformations, it keeps the board valid: the result
+    /// has as many solutions as the original, and a solved board stays
+    /// solved.
+    ///
+    /// # Panics
+    /// If `mapping` is not a permutation of the values from 1 to N2.
+    pub fn relabel(&self, mapping: [u8; N2]) -> SudokuBoard {
+        let mut sorted = mapping;
+        sorted.sort_unstable();
+        assert!(
+            sorted.iter().zip(1..=N2 as u8).all(|(&a, b)| a == b),
+            "the mapping {:?} is not a permutation of the values",
+            mapping
+        );
+
+        let mut board = self.clone();
+        for value in board.0.iter_mut().filter(|value| **value != 0) {
+            *value = mapping[*value as usize - 1];
+        }
+        board
+    }
+
+    /// Returns the canonical form of the board, also called minlex: the
+    /// board with the smallest [SudokuBoard::to_line_string], with empty
+    /// positions before any value, of all the boards that are the same
+    /// puzzle. Those are the ones made by transposing, relabeling the values,
+    /// and reordering the bands, the stacks, the rows inside each band and
+    /// the columns inside each stack.
+    ///
+    /// Two boards are the same puzzle if they have the same canonical form,
+    /// which is useful to find duplicates in a collection. It tries every
+    /// order of the columns and searches the best order of the rows, so it
+    /// is slow compared to solving a board.
+    pub fn canonicalize(&self) -> SudokuBoard {
+        let mut best = Minlex([u8::MAX; SIZE]);
+        for board in [self.clone(), self.transpose()].iter() {
+            for cols in line_orders() {
+                let mut search = MinlexSearch {
+                    board,
+                    cols: &cols,
+                    used: [false; N2],
+                    band: 0,
+                    mapping: [0; N2 + 1],
+                    next_label: 1,
+                };
+                search.rows(0, &mut best);
+            }
+        }
+        SudokuBoard(best.0)
+    }
+}
+
+/// Best board found by [SudokuBoard::canonicalize]. The rows after the last
+/// one that was improved are [u8::MAX] until the search fills them.
+struct Minlex([u8; SIZE]);
+
+/// State of the search of the rows of [SudokuBoard::canonicalize] for an
+/// order of the columns.
+struct MinlexSearch<'a> {
+    board: &'a SudokuBoard,
+    cols: &'a [usize; N2],
+    /// Rows of the board already placed
+    used: [bool; N2],
+    /// Band of the board of the band being placed
+    band: usize,
+    /// New label of each value, `0` if it has not appeared yet
+    mapping: [u8; N2 + 1],
+    next_label: u8,
+}
+
+impl MinlexSearch<'_> {
+    /// Places every row that can go in the row `row` of the result and
+    /// gives the smallest values, and continues with the next row.
+    fn rows(&mut self, row: usize, best: &mut Minlex) {
+        if row == N2 {
+            return;
+        }
+        // the first row of a band starts any band not placed yet, the rest
+        // continue with the band of the first one
+        let band_used = |r: usize| (0..N).any(|i| self.used[(r / N) * N + i]);
+        let candidates: Vec<usize> = (0..N2)
+            .filter(|&r| {
+                if row % N == 0 {
+                    !band_used(r)
+                } else {
+                    !self.used[r] && r / N == self.band
+                }
+            })
+            .collect();
+
+        let mut placed: Vec<(usize, [u8; N2], [u8; N2 + 1], u8)> = Vec::new();
+        for &r in &candidates {
+            // a completely empty row is the same as any other empty row of
+            // its band, only one of them needs to be tried
+            let values = (0..N2).map(|c| self.board.0[to_pos(r, self.cols[c])]);
+            if values.clone().all(|v| v == 0)
+                && placed
+                    .iter()
+                    .any(|&(other, line, _, _)| other / N == r / N && line.iter().all(|&v| v == 0))
+            {
+                continue;
+            }
+            let mut mapping = self.mapping;
+            let mut next_label = self.next_label;
+            let mut line = [0; N2];
+            for (label, v) in line.iter_mut().zip(values) {
+                if v != 0 {
+                    if mapping[v as usize] == 0 {
+                        mapping[v as usize] = next_label;
+                        next_label += 1;
+                    }
+                    *label = mapping[v as usize];
+                }
+            }
+            placed.push((r, line, mapping, next_label));
+        }
+
+        let min = match placed.iter().map(|(_, line, _, _)| *line).min() {
+            Some(min) => min,
+            None => return,
+        };
+        let best_line = &mut best.0[row * N2..(row + 1) * N2];
+        if min[..] > *best_line {
+            return;
+        }
+        if min[..] < *best_line {
+            best_line.copy_from_slice(&min);
+            for v in best.0[(row + 1) * N2..].iter_mut() {
+                *v = u8::MAX;
+            }
+        }
+
+        let saved = (self.band, self.mapping, self.next_label);
+        for (r, line, mapping, next_label) in placed {
+            // a previous row may have made the best smaller
+            if line[..] != best.0[row * N2..(row + 1) * N2] {
+                continue;
+            }
+            self.used[r] = true;
+            self.band = r / N;
+            self.mapping = mapping;
+            self.next_label = next_label;
+            self.rows(row + 1, best);
+            self.used[r] = false;
+        }
+        let (band, mapping, next_label) = saved;
+        self.band = band;
+        self.mapping = mapping;
+        self.next_label = next_label;
+    }
+}
+
+/// All the orders of the rows, or the columns, of a board that keep the
+/// bands, or stacks, together: any order of the bands with any order of the
+/// rows inside each band. `order[i]` is the row that goes to the row `i`.
+fn line_orders() -> Vec<[usize; N2]> {
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+        let mut result = Vec::new();
+        for perm in permutations(n - 1) {
+            for i in 0..n {
+                let mut perm = perm.clone();
+                perm.insert(i, n - 1);
+                result.push(perm);
+            }
+        }
+        result
+    }
+
+    let perms = permutations(N);
+    let mut orders = Vec::new();
+    for bands in &perms {
+        let mut partial: Vec<Vec<usize>> = vec![Vec::new()];
+        for &band in bands {
+            partial = partial
+                .into_iter()
+                .flat_map(|order| {
+                    perms.iter().map(move |inner| {
+                        let mut order = order.clone();
+                        order.extend(inner.iter().map(|&i| band * N + i));
+                        order
+                    })
+                })
+                .collect();
+        }
+        orders.extend(partial.into_iter().map(|order| {
+            let mut array = [0; N2];
+            array.copy_from_slice(&order);
+            array
+        }));
+    }
+    orders
+}
+
+/// Kind of a unit of the board, see [SudokuBoard::units].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitKind {
+    /// A row
+    Row,
+    /// A column
+    Column,
+    /// A group of N x N positions
+    Box,
+}
+
+impl UnitKind {
+    /// Kind of a unit from 0 to [UNITS] - 1 and its index inside the kind.
+    pub(crate) const fn of_unit(unit: usize) -> (UnitKind, usize) {
+        let kind = match unit / N2 {
+            0 => UnitKind::Row,
+            1 => UnitKind::Column,
+            _ => UnitKind::Box,
+        };
+        (kind, unit % N2)
+    }
+}
+
+/// Symmetry of the pattern of given positions of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// No symmetry
+    None,
+    /// Same pattern after rotating the board 180 degrees
+    Rotational180,
+    /// Mirrored over the middle row
+    Horizontal,
+    /// Mirrored over the middle column
+    Vertical,
+    /// Mirrored over the main diagonal, from the top left to the bottom right
+    Diagonal,
+}
+
+impl Symmetry {
+    /// Returns all the symmetries
+    pub const fn all() -> &'static [Symmetry; 5] {
+        &[
+            Symmetry::None,
+            Symmetry::Rotational180,
+            Symmetry::Horizontal,
+            Symmetry::Vertical,
+            Symmetry::Diagonal,
+        ]
+    }
+
+    /// Returns the position that matches `pos` with this symmetry. For
+    /// [Symmetry::None] it is the same position.
+    pub const fn mirror(&self, pos: usize) -> usize {
+        let (row, col) = to_row_col(pos);
+        match self {
+            Symmetry::None => pos,
+            Symmetry::Rotational180 => to_pos(N2 - 1 - row, N2 - 1 - col),
+            Symmetry::Horizontal => to_pos(N2 - 1 - row, col),
+            Symmetry::Vertical => to_pos(row, N2 - 1 - col),
+            Symmetry::Diagonal => to_pos(col, row),
+        }
+    }
+}
+
+/// A position of the board with its coordinates and its value, see
+/// [SudokuBoard::cells].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    /// Position, from 0 to [SIZE] - 1
+    pub pos: usize,
+    /// Row of the position
+    pub row: usize,
+    /// Column of the position
+    pub col: usize,
+    /// Index of the group (box) of the position, in reading order
+    pub box_index: usize,
+    /// Value of the position, 0 if empty
+    pub value: u8,
+}
+
+/// Like [Cell] but with a mutable reference to the value, see
+/// [SudokuBoard::cells_mut].
+#[derive(Debug, PartialEq, Eq)]
+pub struct CellMut<'a> {
+    /// Position, from 0 to [SIZE] - 1
+    pub pos: usize,
+    /// Row of the position
+    pub row: usize,
+    /// Column of the position
+    pub col: usize,
+    /// Index of the group (box) of the position, in reading order
+    pub box_index: usize,
+    /// Value of the position, 0 if empty
+    pub value: &'a mut u8,
+}
+
+/// Settings of [SudokuBoard::to_svg_with]
+#[cfg(feature = "svg")]
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// Width and height of every cell, in pixels. `40` by default.
+    pub cell_size: u32,
+    /// Font family of the values. `"sans-serif"` by default.
+    pub font: String,
+    /// If the candidates of the empty cells are drawn, like pencil marks.
+    /// `false` by default.
+    pub show_candidates: bool,
+}
+
+#[cfg(feature = "svg")]
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 40,
+            font: String::from("sans-serif"),
+            show_candidates: false,
+        }
+    }
+}
+
+// Interface
+impl SudokuBoard {
+    /// Returns all the values in reading order, 0 for the empty positions,
+    /// to read the whole board at once.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Iterates over all the positions in reading order, with their
+    /// coordinates.
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.0.iter().enumerate().map(|(pos, &value)| {
+            let (row, col) = to_row_col(pos);
+            Cell {
+                pos,
+                row,
+                col,
+                box_index: group_index(pos),
+                value,
+            }
+        })
+    }
+
+    /// Like [SudokuBoard::cells] but the values can be changed, for example
+    /// to transform the whole board.
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = CellMut<'_>> + '_ {
+        self.0.iter_mut().enumerate().map(|(pos, value)| {
+            let (row, col) = to_row_col(pos);
+            CellMut {
+                pos,
+                row,
+                col,
+                box_index: group_index(pos),
+                value,
+            }
+        })
+    }
+
+    /// Sets `value` at `pos`. Unlike writing through the index, it fails if
+    /// the position is out of the board or the value is not from 1 to 9.
+    /// Empty a position with [SudokuBoard::unset].
+    ///
+    /// It does not check if the value is already in a peer position, use
+    /// [SudokuBoard::place] for that.
+    pub fn set(&mut self, pos: usize, value: u8) -> Result<(), &'static str> {
+        if pos >= SIZE {
+            return Err("Position out of the board");
+        }
+        if value == 0 || value > N2 as u8 {
+            return Err("Value out of range");
+        }
+        self.0[pos] = value;
+        Ok(())
+    }
+
+    /// Like [SudokuBoard::set] but it also fails if the move is not legal,
+    /// see [SudokuBoard::is_valid].
+    pub fn place(&mut self, pos: usize, value: u8) -> Result<(), &'static str> {
+        if pos < SIZE && !self.is_valid(pos, value) {
+            return Err("Value already in a peer position");
+        }
+        self.set(pos, value)
+    }
+
+    /// Empties the position `pos`.
+    ///
+    /// # Panics
+    /// If `pos` is out of the board.
+    pub fn unset(&mut self, pos: usize) {
+        self.0[pos] = 0;
+    }
+
+    /// Iterates over the values of the groups (boxes), in reading order.
+    pub fn boxes(&self) -> impl Iterator<Item = [u8; N2]> + '_ {
+        (0..N2).map(move |index| self.values_of(group_positions_by_index(index)))
+    }
+
+    /// Iterates over the values of all the units: first the rows, then the
+    /// columns and then the groups, each one tagged with its [UnitKind].
+    pub fn units(&self) -> impl Iterator<Item = ([u8; N2], UnitKind)> + '_ {
+        (0..UNITS).map(move |unit| {
+            let (kind, _) = UnitKind::of_unit(unit);
+            (self.values_of(unit_positions(unit).iter().copied()), kind)
+        })
+    }
+
+    /// Returns the board as an HTML `<table>` with the class `sudoku`.
+    ///
+    /// There are no inline styles, every cell has classes to style it:
+    /// - `given` or `blank` if the cell has a value or is empty.
+    /// - `box-right` and `box-bottom` in the cells next to the border between
+    ///   two groups, to draw it thicker.
+    #[cfg(feature = "html")]
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<table class=\"sudoku\">\n");
+        for row in 0..N2 {
+            html.push_str("<tr>");
+            for col in 0..N2 {
+                let value = self.0[to_pos(row, col)];
+                let mut classes = vec![if value != 0 { "given" } else { "blank" }];
+                if col % N == N - 1 && col != N2 - 1 {
+                    classes.push("box-right");
+                }
+                if row % N == N - 1 && row != N2 - 1 {
+                    classes.push("box-bottom");
+                }
+                html.push_str(&format!("<td class=\"{}\">", classes.join(" ")));
+                if value != 0 {
+                    html.push_str(&value.to_string());
+                }
+                html.push_str("</td>");
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+
+    /// Returns the board in the pretty format of [fmt::Display], but with
+    /// the candidates of the empty positions, like pencil marks. Each
+    /// position is a 3x3 grid where the candidate `n` is in the place of the
+    /// `n`-th value counting by rows, and blank if it was eliminated. A
+    /// position with a value shows it in the center, like `(5)`.
+    pub fn to_pencil_string(&self) -> String {
+        let mut pencil = String::new();
+        fmt_pencil(&mut pencil, self).expect("Writing to a String can not fail");
+        pencil
+    }
+
+    /// Returns the board as a self-contained SVG image with
+    /// [SvgOptions::default].
+    #[cfg(feature = "svg")]
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with(&SvgOptions::default())
+    }
+
+    /// Returns the board as a self-contained SVG image, with the lines
+    /// between groups thicker than the rest, like [fmt::Display].
+    #[cfg(feature = "svg")]
+    pub fn to_svg_with(&self, options: &SvgOptions) -> String {
+        let cell = options.cell_size;
+        let size = cell * N2 as u32;
+        let font = options
+            .font
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;");
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n",
+            size = size
+        );
+        svg.push_str(&format!(
+            "<rect width=\"{size}\" height=\"{size}\" fill=\"white\"/>\n",
+            size = size
+        ));
+
+        for i in 0..=N2 as u32 {
+            let width = if i as usize % N == 0 { 3 } else { 1 };
+            let at = i * cell;
+            svg.push_str(&format!(
+                "<line x1=\"{at}\" y1=\"0\" x2=\"{at}\" y2=\"{size}\" stroke=\"black\" stroke-width=\"{width}\"/>\n",
+                at = at,
+                size = size,
+                width = width
+            ));
+            svg.push_str(&format!(
+                "<line x1=\"0\" y1=\"{at}\" x2=\"{size}\" y2=\"{at}\" stroke=\"black\" stroke-width=\"{width}\"/>\n",
+                at = at,
+                size = size,
+                width = width
+            ));
+        }
+
+        let text = |x: u32, y: u32, font_size: u32, class: &str, value: u8| {
+            format!(
+                "<text class=\"{}\" x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                class, x, y, font, font_size, value
+            )
+        };
+        for (pos, &value) in self.0.iter().enumerate() {
+            let (row, col) = to_row_col(pos);
+            let (x, y) = (col as u32 * cell, row as u32 * cell);
+            if value != 0 {
+                svg.push_str(&text(
+                    x + cell / 2,
+                    y + cell / 2,
+                    cell * 3 / 5,
+                    "given",
+                    value,
+                ));
+            } else if options.show_candidates {
+                // each candidate in its own place of a N x N grid in the cell
+                let mark = cell / N as u32;
+                for candidate in self.candidates(pos) {
+                    let i = candidate as u32 - 1;
+                    let (mark_x, mark_y) = (i % N as u32, i / N as u32);
+                    svg.push_str(&text(
+                        x + mark_x * mark + mark / 2,
+                        y + mark_y * mark + mark / 2,
+                        mark * 3 / 4,
+                        "candidate",
+                        candidate,
+                    ));
+                }
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn values_of(&self, positions: impl Iterator<Item = usize>) -> [u8; N2] {
+        let mut values = [0; N2];
+        for (value, pos) in values.iter_mut().zip(positions) {
+            *value = self.0[pos];
+        }
+        values
+    }
+
+    /// Returns the values of the board as a grid of rows, like the ones
+    /// accepted by `try_from`.
+    pub fn to_grid(&self) -> [[u8; N2]; N2] {
+        let mut grid = [[0; N2]; N2];
+        for (pos, &value) in self.0.iter().enumerate() {
+            let (row, col) = to_row_col(pos);
+            grid[row][col] = value;
+        }
+        grid
+    }
+
+    /// Returns the 1 line representation of the board.
+    /// Scanning row by row. A dot means an empty position.
+    pub fn to_line_string(&self) -> String {
+        self.0.iter().map(|&x| symbol(x, N2)).collect()
+    }
+
+    /// Parses the 1 line representation of a board, made by
+    /// [SudokuBoard::to_line_string]. The same as the `TryFrom<&str>`
+    /// conversion, where the accepted input is explained.
+    pub fn from_line_string(s: &str) -> Result<SudokuBoard, ParseError> {
+        SudokuBoard::try_from(s)
+    }
+
+    /// Packs the board in 4 bits per position, two positions per byte, the
+    /// first one in the high nibble. With an odd number of positions, the low
+    /// nibble of the last byte is `0`. Half the size of
+    /// [SudokuBoard::to_line_string].
+    pub fn to_packed(&self) -> Vec<u8> {
+        self.0
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+            .collect()
+    }
+
+    /// Unpacks a board packed with [SudokuBoard::to_packed].
+    ///
+    /// Fails with [ParseError::WrongLength] if there are not exactly the
+    /// bytes of a packed board, or with [ParseError::ValueOutOfRange] if a
+    /// value does not fit in the board. A non zero unused nibble at the end is
+    /// out of range at the position after the last one.
+    pub fn from_packed(bytes: &[u8]) -> Result<SudokuBoard, ParseError> {
+        if bytes.len() != (SIZE + 1) / 2 {
+            return Err(ParseError::WrongLength {
+                expected: (SIZE + 1) / 2,
+                got: bytes.len(),
+            });
+        }
+        let mut board = [0; SIZE];
+        for (pos, value) in board.iter_mut().enumerate() {
+            let byte = bytes[pos / 2];
+            *value = if pos % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            if *value > N2 as u8 {
+                return Err(ParseError::ValueOutOfRange { pos, value: *value });
+            }
+        }
+        if SIZE % 2 == 1 && bytes[SIZE / 2] & 0x0f != 0 {
+            return Err(ParseError::ValueOutOfRange {
+                pos: SIZE,
+                value: bytes[SIZE / 2] & 0x0f,
+            });
+        }
+        Ok(SudokuBoard(board))
+    }
+
+    /// Returns a line of the format used by public bulk datasets:
+    /// `id,puzzle,solution`, where a `0` is an empty position. Without a
+    /// solution, the line ends with the comma.
+    pub fn to_exchange(&self, id: &str, solution: Option<&SudokuBoard>) -> String {
+        let digits = |board: &SudokuBoard| -> String {
+            board
+                .0
+                .iter()
+                .map(|&value| (b'0' + value) as char)
+                .collect()
+        };
+        format!(
+            "{},{},{}",
+            id,
+            digits(self),
+            solution.map(digits).unwrap_or_default()
+        )
+    }
+
+    /// Reads a line of a public bulk dataset: `id,puzzle,solution` like
+    /// [SudokuBoard::to_exchange], the Kaggle style `puzzle,solution` or a
+    /// puzzle with a prefix like the clue count. The fields can be separated
+    /// by commas or spaces, and the first two fields of [SIZE] characters are
+    /// the puzzle and the solution.
+    ///
+    /// Fails if there is no field of [SIZE] characters or if they are not
+    /// valid boards.
+    pub fn from_exchange(line: &str) -> Result<(SudokuBoard, Option<SudokuBoard>), ParseError> {
+        let mut boards = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|field| field.chars().count() == SIZE)
+            .map(|field| SudokuBoard::parse_concatenated(field).map(|mut b| b.remove(0)));
+
+        let puzzle = match boards.next() {
+            Some(puzzle) => puzzle?,
+            None => {
+                return Err(ParseError::WrongLength {
+                    expected: SIZE,
+                    got: line
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .map(|field| field.chars().count())
+                        .max()
+                        .unwrap_or(0),
+                })
+            }
+        };
+        let solution = boards.next().transpose()?;
+        Ok((puzzle, solution))
+    }
+
+    /// Parses multiple boards concatenated in a single line, like
+    /// `"<board 1><board 2>..."`. Each board is [SIZE] characters long, where
+    /// a `.` or a `0` is an empty position.
+    ///
+    /// Fails if there is an invalid character or if the length of the line is
+    /// not a multiple of [SIZE].
+    pub fn parse_concatenated(s: &str) -> Result<Vec<SudokuBoard>, ParseError> {
+        let s = s.trim();
+        if let Some((pos, ch)) = s
+            .chars()
+            .enumerate()
+            .find(|&(_, c)| !c.is_ascii() || symbol_value(c, N2).is_none())
+        {
+            return Err(ParseError::InvalidCharacter { pos, ch });
+        }
+        // only ascii characters, so bytes are characters
+        if s.len() % SIZE != 0 {
+            return Err(ParseError::WrongLength {
+                expected: (s.len() / SIZE + 1) * SIZE,
+                got: s.len(),
+            });
+        }
+
+        Ok(s.as_bytes()
+            .chunks(SIZE)
+            .map(|chunk| {
+                let mut board = SudokuBoard::default();
+                for (value, &c) in board.0.iter_mut().zip(chunk) {
+                    *value = symbol_value(c as char, N2).unwrap_or(0);
+                }
+                board
+            })
+            .collect())
+    }
+
+    /// Parses a board in the SadMan Software format (`.sdk` files): one row
+    /// per line, where a `.` or a `0` is an empty position. Lines starting
+    /// with `#`, like the `#A` author, `#D` description or `#C` comment
+    /// metadata, and blank lines are skipped.
+    ///
+    /// Fails like the `TryFrom<&str>` conversion, with the rows read as a
+    /// single line.
+    pub fn from_sdk(input: &str) -> Result<SudokuBoard, ParseError> {
+        let grid: String = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        SudokuBoard::try_from(grid.as_str())
+    }
+
+    /// Returns the board in the SadMan Software format (`.sdk` files), one
+    /// row per line and `.` for the empty positions, without metadata. See
+    /// [SudokuBoard::from_sdk].
+    pub fn to_sdk(&self) -> String {
+        let mut sdk = String::with_capacity(SIZE + N2);
+        for row in self.0.chunks(N2) {
+            sdk.extend(row.iter().map(|&value| symbol(value, N2)));
+            sdk.push('\n');
+        }
+        sdk
+    }
+}
+
+/// Error produced when parsing a [SudokuBoard] from text or values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input does not have the expected number of characters.
+    WrongLength {
+        /// Expected number of characters
+        expected: usize,
+        /// Number of characters found
+        got: usize,
+    },
+    /// A character that is neither a value nor an empty position.
+    InvalidCharacter {
+        /// Position of the character in the input
+        pos: usize,
+        /// The invalid character
+        ch: char,
+    },
+    /// A value that does not fit in the board.
+    ValueOutOfRange {
+        /// Position of the value
+        pos: usize,
+        /// The invalid value
+        value: u8,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::WrongLength { expected, got } => {
+                write!(f, "Invalid length {}, expected {}", got, expected)
+            }
+            ParseError::InvalidCharacter { pos, ch } => {
+                write!(f, "Invalid character '{}' at {}", ch, pos)
+            }
+            ParseError::ValueOutOfRange { pos, value } => {
+                write!(f, "Value {} at {} must be between 0 and {}", value, pos, N2)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Character of a value in a board with `n2` values. A `.` is an empty
+/// position. Boards up to 9x9 use the digits, and bigger ones, like 16x16,
+/// use the hexadoku notation: `0` to `9` and `A` to `F` for 1 to 16.
+fn symbol(value: u8, n2: usize) -> char {
+    match value {
+        0 => '.',
+        v if n2 > 9 => {
+            core::char::from_digit(v as u32 - 1, 16).map_or('?', |c| c.to_ascii_uppercase())
+        }
+        v => (b'0' + v) as char,
+    }
+}
+
+/// Value of a character made by [symbol]. The hexadecimal letters are
+/// case-insensitive, and in boards up to 9x9 a `0` is also empty.
+fn symbol_value(c: char, n2: usize) -> Option<u8> {
+    if c == '.' {
+        return Some(0);
+    }
+    let value = if n2 > 9 {
+        c.to_digit(16)? as u8 + 1
+    } else {
+        c.to_digit(10)? as u8
+    };
+    if value as usize <= n2 {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+impl Default for SudokuBoard {
+    fn default() -> Self {
+        SudokuBoard([0; SIZE])
+    }
+}
+
+impl fmt::Display for SudokuBoard {
+    /// Pretty format for a sudoku
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.to_line_string())
+        } else {
+            fmt_pretty(f, self, None)
+        }
+    }
+}
+
+/// Pretty format of a board that marks the cells given in a puzzle.
+/// Created with [SudokuBoard::display_with_givens].
+pub struct WithGivens<'a> {
+    board: &'a SudokuBoard,
+    givens: &'a SudokuBoard,
+}
+
+impl SudokuBoard {
+    /// Pretty format for the board where the cells that are not empty in
+    /// `givens` are surrounded by parentheses, like `(5)`. Useful to show
+    /// a solution telling apart the values given in the puzzle.
+    ///
+    /// # Example
+    /// ```
+    /// use sudoku::prelude::*;
+    ///
+    /// let puzzle = SudokuPuzzle::prepare().show_solution(true).generate();
+    /// let solution = puzzle.solution.as_ref().unwrap();
+    /// println!("{}", solution.display_with_givens(&puzzle.puzzle));
+    /// ```
+    pub fn display_with_givens<'a>(&'a self, givens: &'a SudokuBoard) -> WithGivens<'a> {
+        WithGivens {
+            board: self,
+            givens,
+        }
+    }
+}
+
+impl fmt::Display for WithGivens<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pretty(f, self.board, Some(self.givens))
+    }
+}
+
+fn fmt_pretty(
+    f: &mut fmt::Formatter,
+    board: &SudokuBoard,
+    givens: Option<&SudokuBoard>,
+) -> fmt::Result {
+    fn fmt_row(f: &mut fmt::Formatter, row: &[u8], givens: Option<&[u8]>) -> fmt::Result {
+        write!(f, "║")?;
+        for (i, &n) in row.iter().enumerate().take(N2) {
+            let is_given = givens.map_or(false, |g| g[i] != 0);
+            if n != 0 && is_given {
+                write!(f, "({})", symbol(n, N2))?;
+            } else if n != 0 {
+                write!(f, "{: ^3}", symbol(n, N2))?;
+            } else {
+                write!(f, "   ")?;
+            }
+            if i % N != N - 1 {
+                write!(f, "│")?;
+            } else {
+                write!(f, "║")?;
+            }
+        }
+        writeln!(f)
+    }
+    fmt_border(f, 3, '╔', '═', '╦', '╗', '═')?;
+    for i in 0..N2 {
+        let range = i * N2..(i + 1) * N2;
+        fmt_row(f, &board.0[range.clone()], givens.map(|g| &g.0[range]))?;
+        if i != N2 - 1 {
+            if i % N != N - 1 {
+                fmt_border(f, 3, '║', '┼', '║', '║', '─')?;
+            } else {
+                fmt_border(f, 3, '╠', '═', '╬', '╣', '═')?;
+            }
+        }
+    }
+    fmt_border(f, 3, '╚', '═', '╩', '╝', '═')
+}
+
+/// Pretty format like [fmt_pretty], but each position is a grid of N x N
+/// characters with its candidates, see [SudokuBoard::to_pencil_string].
+fn fmt_pencil(f: &mut impl fmt::Write, board: &SudokuBoard) -> fmt::Result {
+    let candidates = board.candidates_mask();
+    fmt_border(f, N, '╔', '═', '╦', '╗', '═')?;
+    for row in 0..N2 {
+        for line in 0..N {
+            write!(f, "║")?;
+            for col in 0..N2 {
+                let pos = to_pos(row, col);
+                let value = board.0[pos];
+                if value != 0 {
+                    let center = if line == N / 2 {
+                        format!("({})", symbol(value, N2))
+                    } else {
+                        String::new()
+                    };
+                    write!(f, "{: ^width$}", center, width = N)?;
+                } else {
+                    for mark in (line * N + 1..=(line + 1) * N).map(|mark| mark as u8) {
+                        if candidates[pos] & value_bit(mark) != 0 {
+                            write!(f, "{}", symbol(mark, N2))?;
+                        } else {
+                            write!(f, " ")?;
+                        }
+                    }
+                }
+                if col % N != N - 1 {
+                    write!(f, "│")?;
+                } else {
+                    write!(f, "║")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        if row != N2 - 1 {
+            if row % N != N - 1 {
+                fmt_border(f, N, '║', '┼', '║', '║', '─')?;
+            } else {
+                fmt_border(f, N, '╠', '═', '╬', '╣', '═')?;
+            }
+        }
+    }
+    fmt_border(f, N, '╚', '═', '╩', '╝', '═')
+}
+
+/// Horizontal border of the pretty formats, with positions of `width`
+/// characters.
+fn fmt_border(
+    f: &mut impl fmt::Write,
+    width: usize,
+    left: char,
+    num_sep: char,
+    group_sep: char,
+    right: char,
+    regular: char,
+) -> fmt::Result {
+    let num_border: String = core::iter::repeat(regular).take(width).collect();
+    write!(f, "{}", left)?;
+    for i in 0..N2 {
+        write!(f, "{}", num_border)?;
+        if i != N2 - 1 {
+            if i % N != N - 1 {
+                write!(f, "{}", num_sep)?;
+            } else {
+                write!(f, "{}", group_sep)?;
+            }
+        }
+    }
+    writeln!(f, "{}", right)
+}
+
+impl TryFrom<&str> for SudokuBoard {
+    type Error = ParseError;
+
+    /// Tries to converts a sudoku board string representation into a sudoku board.
+    ///
+    /// The values are the digits `1` to `9`, and both `.` and `0` are empty
+    /// positions, so the common 81 digit format is also accepted. Any other
+    /// character is a [ParseError::InvalidCharacter].
+    ///
+    /// Boards bigger than 9x9 use the hexadoku notation, `0` to `9` and `A`
+    /// to `F` for the values 1 to 16, so only `.` is empty.
+    fn try_from(s: &str) -> Result<SudokuBoard, Self::Error> {
+        let len = s.chars().count();
+        if len != SIZE {
+            return Err(ParseError::WrongLength {
+                expected: SIZE,
+                got: len,
+            });
+        }
+
+        let mut board = [0; SIZE];
+        for (pos, (value, ch)) in board.iter_mut().zip(s.chars()).enumerate() {
+            *value = symbol_value(ch, N2).ok_or(ParseError::InvalidCharacter { pos, ch })?;
+        }
+        Ok(SudokuBoard(board))
+    }
+}
+
+impl TryFrom<[u8; SIZE]> for SudokuBoard {
+    type Error = ParseError;
+
+    fn try_from(arr: [u8; SIZE]) -> Result<SudokuBoard, Self::Error> {
+        match arr.iter().position(|&d| d > N2 as u8) {
+            None => Ok(SudokuBoard(arr)),
+            Some(pos) => Err(ParseError::ValueOutOfRange {
+                pos,
+                value: arr[pos],
+            }),
+        }
+    }
+}
+
+impl TryFrom<Vec<u8>> for SudokuBoard {
+    type Error = ParseError;
+    fn try_from(vec: Vec<u8>) -> Result<SudokuBoard, Self::Error> {
+        vec.as_slice().try_into()
+    }
+}
+
+impl TryFrom<&[u8]> for SudokuBoard {
+    type Error = ParseError;
+    fn try_from(slice: &[u8]) -> Result<SudokuBoard, Self::Error> {
+        <[u8; SIZE]>::try_from(slice)
+            .map_err(|_| ParseError::WrongLength {
+                expected: SIZE,
+                got: slice.len(),
+            })
+            .and_then(SudokuBoard::try_from)
+    }
+}
+
+impl TryFrom<[[u8; N2]; N2]> for SudokuBoard {
+    type Error = ParseError;
+
+    /// Converts a grid of rows into a board. The inverse of
+    /// [SudokuBoard::to_grid].
+    fn try_from(grid: [[u8; N2]; N2]) -> Result<SudokuBoard, Self::Error> {
+        let mut board = [0; SIZE];
+        for (pos, value) in board.iter_mut().enumerate() {
+            let (row, col) = to_row_col(pos);
+            *value = grid[row][col];
+        }
+        SudokuBoard::try_from(board)
+    }
+}
+
+impl From<SudokuBoard> for [u8; SIZE] {
+    fn from(board: SudokuBoard) -> Self {
+        board.0
+    }
+}
+
+impl From<SudokuBoard> for Vec<u8> {
+    fn from(board: SudokuBoard) -> Self {
+        board.0.to_vec()
+    }
+}
+
+impl From<&SudokuBoard> for String {
+    /// The 1 line representation, see [SudokuBoard::to_line_string]
+    fn from(board: &SudokuBoard) -> Self {
+        board.to_line_string()
+    }
+}
+
+/// Serialized as the 1 line representation, see [SudokuBoard::to_line_string]
+#[cfg(feature = "serde")]
+impl serde::Serialize for SudokuBoard {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_line_string())
+    }
+}
+
+/// Deserialized from the 1 line representation, with the same checks as
+/// `TryFrom<&str>`
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SudokuBoard {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LineVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for LineVisitor {
+            type Value = SudokuBoard;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a sudoku board in a line of {} characters", SIZE)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                SudokuBoard::try_from(s).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(LineVisitor)
+    }
+}
+
+/// Boards with any value from 0 to 9 in each position, so they may be
+/// invalid or unsolvable.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SudokuBoard {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut board = [0; SIZE];
+        for value in board.iter_mut() {
+            *value = u.int_in_range(0..=N2 as u8)?;
+        }
+        Ok(Self(board))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (SIZE, Some(SIZE))
+    }
+}
+
+impl AsRef<[u8; SIZE]> for SudokuBoard {
+    fn as_ref(&self) -> &[u8; SIZE] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SudokuBoard {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl core::ops::Deref for SudokuBoard {
+    type Target = [u8; SIZE];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl core::ops::DerefMut for SudokuBoard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn line_string() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert_eq!(
+            s.to_line_string(),
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3"
+        );
+        assert_eq!(SudokuBoard::from_line_string(&s.to_line_string()), Ok(s));
+        assert!(SudokuBoard::from_line_string("..2").is_err());
+    }
+
+    #[test]
+    fn line_string_zeroes() {
+        let s = SudokuBoard::try_from(
+            "002....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert_eq!(
+            s.to_line_string(),
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3"
+        );
+
+        let dots = ".".repeat(SIZE);
+        let zeroes = "0".repeat(SIZE);
+        assert_eq!(
+            SudokuBoard::try_from(zeroes.as_str()).unwrap(),
+            SudokuBoard::try_from(dots.as_str()).unwrap()
+        );
+        assert_eq!(
+            SudokuBoard::try_from(zeroes.as_str()).unwrap(),
+            SudokuBoard::default()
+        );
+
+        // there is no digit over 9, but the hexadoku ones are not accepted
+        let mut line = "0".repeat(SIZE - 1);
+        line.insert(10, 'A');
+        let err = SudokuBoard::try_from(line.as_str()).unwrap_err();
+        assert_eq!(err, ParseError::InvalidCharacter { pos: 10, ch: 'A' });
+        assert_eq!(err.to_string(), "Invalid character 'A' at 10");
+    }
+
+    #[test]
+    fn parse_concatenated() {
+        let a = "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3";
+        let b = a.replace('.', "0");
+        let boards = SudokuBoard::parse_concatenated(&format!("{}{}", a, b)).unwrap();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[0], boards[1]);
+        assert_eq!(boards[0].to_line_string(), a);
+
+        assert_eq!(
+            SudokuBoard::parse_concatenated(&format!("{}{}", a, &b[1..])),
+            Err(ParseError::WrongLength {
+                expected: 2 * SIZE,
+                got: 2 * SIZE - 1
+            })
+        );
+        assert_eq!(
+            SudokuBoard::parse_concatenated(&format!("{}ñ{}", a, &b[1..])),
+            Err(ParseError::InvalidCharacter {
+                pos: SIZE, ch: 'ñ'
+            })
+        );
+    }
+
+    #[test]
+    fn display_with_givens() {
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut solution = puzzle.clone();
+        solution.solve();
+
+        let plain = solution.to_string();
+        let marked = solution.display_with_givens(&puzzle).to_string();
+        assert_eq!(plain.lines().count(), marked.lines().count());
+        assert!(marked.contains("║ 5 │ 4 │(2)║"));
+        let givens = puzzle.iter().filter(|&&x| x != 0).count();
+        assert_eq!(marked.matches('(').count(), givens);
+        assert_eq!(
+            puzzle
+                .display_with_givens(&SudokuBoard::default())
+                .to_string(),
+            puzzle.to_string()
+        );
+    }
+
+    #[test]
+    fn solve_1() {
+        let mut s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        s.solve();
+        assert_eq!(
+            s.to_line_string(),
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+        );
+    }
+
+    #[test]
+    fn solve_all_1() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let solutions = s.solve_all(10);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(
+            solutions[0].to_line_string(),
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+        );
+    }
+
+    #[test]
+    fn count_1() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert_eq!(s.count_solutions(10), 1);
+    }
+
+    #[test]
+    fn count_bounded() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        assert_eq!(s.count_solutions_bounded(10, usize::MAX), (2, false));
+
+        let (count, exhausted) = SudokuBoard::default().count_solutions_bounded(usize::MAX, 1000);
+        assert!(exhausted);
+        assert!(count > 0 && count < 1000);
+    }
+
+    #[test]
+    fn solve_all_more() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        let solutions = s.solve_all(10);
+        assert_eq!(solutions.len(), 2);
+        let solutions = solutions
+            .iter()
+            .map(|s| s.to_line_string())
+            .collect::<Vec<_>>();
+        assert!(solutions.contains(
+            &"926571483351486279874923516582367194149258267763194825238749651617835942495612738"
+                .to_string()
+        ));
+        assert!(solutions.contains(
+            &"926571483351486279874923516582367194149258267763149825238794651617835942495612738"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn solutions() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        assert_eq!(s.solutions().collect::<Vec<_>>(), s.solve_all(10));
+
+        let empty = SudokuBoard::default();
+        let first: Vec<_> = empty.solutions().take(5).collect();
+        assert_eq!(first, empty.solve_all(5));
+
+        let solved = s.solve_all(1).pop().unwrap();
+        assert_eq!(solved.solutions().collect::<Vec<_>>(), vec![solved.clone()]);
+
+        // no value fits at the end of the first row
+        let mut unsolvable = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 1).zip(1..) {
+            unsolvable[pos] = value;
+        }
+        unsolvable[N2 + N2 - 1] = N2 as u8;
+        assert_eq!(unsolvable.solutions().next(), None);
+    }
+
+    #[test]
+    fn solution_differences() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        let (solutions, differences) = s.solution_differences(10);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(differences, vec![49, 50, 58, 59]);
+
+        let (solutions, differences) = s.solution_differences(1);
+        assert_eq!(solutions.len(), 1);
+        assert!(differences.is_empty());
+    }
+
+    #[test]
+    fn count_more() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        assert_eq!(s.count_solutions(10), 2);
+    }
+
+    #[test]
+    fn explain_unsolvable() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert_eq!(s.explain_unsolvable(), None);
+        assert_eq!(SudokuBoard::default().explain_unsolvable(), None);
+
+        let mut conflict = s.clone();
+        conflict[0] = 2;
+        assert_eq!(
+            conflict.explain_unsolvable(),
+            Some(Unsolvable::Conflict(0, 2))
+        );
+
+        let mut no_candidates = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 1).zip(1..) {
+            no_candidates[pos] = value;
+        }
+        no_candidates[to_pos(1, N2 - 1)] = N2 as u8;
+        assert_eq!(
+            no_candidates.explain_unsolvable(),
+            Some(Unsolvable::NoCandidates(N2 - 1))
+        );
+
+        // the two last positions of the first row can only have an 8
+        let mut no_solution = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 2).zip(1..) {
+            no_solution[pos] = value;
+        }
+        no_solution[to_pos(1, N2 - 2)] = N2 as u8;
+        assert_eq!(
+            no_solution.explain_unsolvable(),
+            Some(Unsolvable::NoSolution)
+        );
+    }
+
+    #[test]
+    fn solve_recording() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut board = s.clone();
+        let filled = board.solve_recording().unwrap();
+        assert_eq!(filled.len(), s.empty_count());
+        assert!(board.is_solved());
+        assert!(filled
+            .iter()
+            .all(|&(pos, value)| s[pos] == 0 && board[pos] == value));
+        assert!(filled.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        for (pos, _) in filled {
+            board[pos] = 0;
+        }
+        assert_eq!(board, s);
+
+        // no value fits at the end of the first row
+        let mut unsolvable = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 1).zip(1..) {
+            unsolvable[pos] = value;
+        }
+        unsolvable[N2 + N2 - 1] = N2 as u8;
+        let before = unsolvable.clone();
+        assert_eq!(unsolvable.solve_recording(), None);
+        assert_eq!(unsolvable, before);
+    }
+
+    #[test]
+    fn solve_with_candidates() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let solution = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+
+        // the same puzzle only with pencilmarks
+        let mask = s.candidates_mask();
+        let sukaku: String = (0..SIZE)
+            .flat_map(|pos| {
+                let (value, mask) = (s[pos], mask[pos]);
+                (1..=N2 as u8).map(move |n| {
+                    if value == n || mask & value_bit(n) != 0 {
+                        (b'0' + n) as char
+                    } else {
+                        '.'
+                    }
+                })
+            })
+            .collect();
+        let candidates = SudokuBoard::parse_sukaku(&sukaku).unwrap();
+        let mut board = SudokuBoard::default();
+        assert!(board.solve_with_candidates(&candidates));
+        assert_eq!(board, solution);
+
+        // a given that is not a candidate
+        let mut board = s.clone();
+        let mut wrong = candidates;
+        wrong[2] = [false; N2];
+        assert!(!board.solve_with_candidates(&wrong));
+        assert_eq!(board, s);
+
+        assert_eq!(
+            SudokuBoard::parse_sukaku(&sukaku[1..]),
+            Err(ParseError::WrongLength {
+                expected: 729,
+                got: 728
+            })
+        );
+        let swapped = format!("21{}", &sukaku[2..]);
+        assert_eq!(
+            SudokuBoard::parse_sukaku(&swapped),
+            Err(ParseError::InvalidCharacter { pos: 0, ch: '2' })
+        );
+    }
+
+    #[test]
+    fn solve_and_count() {
+        let unique = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let solution = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        assert_eq!(unique.solve_and_count(2), (Some(solution), 1));
+
+        let two = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        let (first, count) = two.solve_and_count(10);
+        assert_eq!(count, 2);
+        assert_eq!(first, two.solve_all(1).pop());
+        assert_eq!(two.solve_and_count(1).1, 1);
+
+        // no value fits at the end of the first row
+        let mut unsolvable = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 1).zip(1..) {
+            unsolvable[pos] = value;
+        }
+        unsolvable[N2 + N2 - 1] = N2 as u8;
+        assert_eq!(unsolvable.solve_and_count(2), (None, 0));
+        assert_eq!(unique.solve_and_count(0), (None, 0));
+    }
+
+    #[test]
+    fn has_unique_solution() {
+        let unique = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert!(unique.has_unique_solution());
+        let two = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        assert!(!two.has_unique_solution());
+        assert!(!SudokuBoard::default().has_unique_solution());
+
+        // the search stops as soon as the limit is reached
+        assert_eq!(unique.count_solutions(0), 0);
+        let first = SudokuBoard::default().backtracking(SolveParams::default());
+        let second = SudokuBoard::default().backtracking(SolveParams {
+            max_solutions: 2,
+            ..SolveParams::default()
+        });
+        // one node for each empty position and the solution
+        assert_eq!(first.solution_count, 1);
+        assert_eq!(first.stats.nodes_visited, SIZE + 1);
+        assert_eq!(second.solution_count, 2);
+        assert!(second.stats.nodes_visited < 2 * (SIZE + 1));
+    }
+
+    #[test]
+    fn solve_with_exclusions() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+
+        // the two solutions differ at position 59
+        let mut a = s.clone();
+        assert!(a.solve_with_exclusions(&[(59, 4)]));
+        assert_eq!(
+            a.to_line_string(),
+            "926571483351486279874923516582367194149258267763194825238749651617835942495612738"
+        );
+        let mut b = s.clone();
+        assert!(b.solve_with_exclusions(&[(59, 9)]));
+        assert_eq!(
+            b.to_line_string(),
+            "926571483351486279874923516582367194149258267763149825238794651617835942495612738"
+        );
+
+        let mut c = s.clone();
+        assert!(!c.solve_with_exclusions(&[(59, 4), (59, 9)]));
+        // contradicts a given
+        let mut d = s.clone();
+        assert!(!d.solve_with_exclusions(&[(0, 9)]));
+        assert_eq!(d, s);
+    }
+
+    #[test]
+    fn given_mask_and_complement() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mask = s.given_mask();
+        let complement = s.clue_complement();
+        for pos in 0..SIZE {
+            assert_eq!(mask[pos], s[pos] != 0);
+            assert_eq!(mask[pos], complement[pos] == 0);
+        }
+        assert_eq!(
+            complement.to_line_string(),
+            "54.9716.89173..2.48...427..72385914.4...238.515.467329384715.6269523.4..2...94.8."
+        );
+
+        let conflicting = SudokuBoard::try_from(
+            "11...............................................................................",
+        )
+        .unwrap();
+        assert_eq!(conflicting.clue_complement(), SudokuBoard::default());
+
+        // no conflicts, but the last cell of the first row has no possible value
+        let unsolvable = SudokuBoard::try_from(
+            "12345678.........9...............................................................",
+        )
+        .unwrap();
+        assert_eq!(unsolvable.clue_complement(), SudokuBoard::default());
+    }
+
+    #[test]
+    fn can_share_value() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        // same row
+        assert!(!s.can_share_value(0, 1));
+        // same group
+        assert!(!s.can_share_value(0, 10));
+        // both can be a 4
+        assert!(s.can_share_value(0, 49));
+        // 8 can only be 4 or 8 and 43 can only be 7
+        assert!(!s.can_share_value(8, 43));
+        // 2 is given with a 2 and 13 can only be 8
+        assert!(!s.can_share_value(2, 13));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn generate_with_other_rng() {
+        use rand::rngs::StdRng;
+        let a = SudokuBoard::generate_with(&mut StdRng::seed_from_u64(42));
+        let b = SudokuBoard::generate_with(&mut StdRng::seed_from_u64(42));
+        assert_eq!(a, b);
+        for (pos, &val) in a.0.iter().enumerate() {
+            assert_ne!(val, 0);
+            assert!(a.is_valid(pos, val));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn clue_entropy() {
+        let max = SIZE as f64 * (N2 as f64).log2();
+        assert_eq!(SudokuBoard::default().clue_entropy(), 0.0);
+
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let entropy = s.clue_entropy();
+        assert!(entropy > 0.0 && entropy < max);
+
+        let mut solved = s.clone();
+        solved.solve();
+        assert!((solved.clue_entropy() - max).abs() < 1e-9);
+        assert!(solved.clue_entropy() > entropy);
+
+        // the last cell of the first row has no possible value, so it gives
+        // nothing compared to having it filled
+        let dead = SudokuBoard::try_from(
+            "12345678.........9...............................................................",
+        )
+        .unwrap();
+        let filled = SudokuBoard::try_from(
+            "123456789........9...............................................................",
+        )
+        .unwrap();
+        let diff = filled.clue_entropy() - dead.clue_entropy();
+        assert!((diff - (N2 as f64).log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn generate() {
+        use rand::SeedableRng;
+        use rand_pcg::Pcg64;
+        let s = SudokuBoard::generate(&mut Pcg64::from_entropy());
+        for (pos, &val) in s.0.iter().enumerate() {
+            assert_ne!(val, 0);
+            assert!(s.is_valid(pos, val));
+        }
+    }
+
+    #[test]
+    fn detect_symmetry() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert!(s.detect_symmetry().is_empty());
+        assert!(s.has_symmetry(Symmetry::None));
+
+        let mut s = SudokuBoard::default();
+        s[to_pos(0, 1)] = 1;
+        s[to_pos(8, 7)] = 1;
+        assert_eq!(s.detect_symmetry(), vec![Symmetry::Rotational180]);
+        s[to_pos(1, 0)] = 2;
+        s[to_pos(7, 8)] = 2;
+        assert_eq!(
+            s.detect_symmetry(),
+            vec![Symmetry::Rotational180, Symmetry::Diagonal]
+        );
+        assert_eq!(
+            SudokuBoard::default().detect_symmetry().len(),
+            Symmetry::all().len() - 1
+        );
+    }
+
+    #[test]
+    fn units() {
+        let s = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        let boxes: Vec<_> = s.boxes().collect();
+        assert_eq!(boxes.len(), N2);
+        assert_eq!(boxes[0], [5, 4, 2, 9, 1, 7, 8, 3, 6]);
+        assert_eq!(boxes[8], [9, 6, 2, 4, 1, 7, 5, 8, 3]);
+
+        let units: Vec<_> = s.units().collect();
+        assert_eq!(units.len(), N2 * 3);
+        assert_eq!(units[0], ([5, 4, 2, 9, 7, 1, 6, 3, 8], UnitKind::Row));
+        assert_eq!(units[N2], ([5, 9, 8, 7, 4, 1, 3, 6, 2], UnitKind::Column));
+        assert_eq!(units[N2 * 2], (boxes[0], UnitKind::Box));
+        for (mut values, _) in units {
+            values.sort_unstable();
+            assert_eq!(values, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+    }
+
+    #[test]
+    fn transformations() {
+        let solved = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        let mapping = [9, 8, 7, 6, 5, 4, 3, 2, 1];
+        let transformed = [
+            solved.rotate_90(),
+            solved.reflect_horizontal(),
+            solved.transpose(),
+            solved.relabel(mapping),
+        ];
+        for board in &transformed {
+            board.assert_valid_solution();
+            assert_ne!(*board, solved);
+        }
+        assert_eq!(solved.rotate_90()[N2 - 1], solved[0]);
+        assert_eq!(
+            solved.rotate_90().rotate_90().rotate_90().rotate_90(),
+            solved
+        );
+        assert_eq!(solved.reflect_horizontal().reflect_horizontal(), solved);
+        assert_eq!(solved.transpose()[1], solved[N2]);
+        assert_eq!(solved.relabel(mapping)[0], 5);
+        assert_eq!(solved.relabel(mapping).relabel(mapping), solved);
+
+        // the solution of the transformed puzzle is the transformed solution
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut rotated = puzzle.rotate_90().relabel(mapping);
+        assert_eq!(rotated.count_solutions(2), 1);
+        assert!(rotated.solve());
+        assert_eq!(rotated, solved.rotate_90().relabel(mapping));
+    }
+
+    #[test]
+    #[should_panic]
+    fn relabel_not_permutation() {
+        SudokuBoard::default().relabel([1, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn cells() {
+        let s = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        let cells: Vec<Cell> = s.cells().collect();
+        assert_eq!(cells.len(), SIZE);
+        assert_eq!(
+            cells[40],
+            Cell {
+                pos: 40,
+                row: 4,
+                col: 4,
+                box_index: 4,
+                value: 2
+            }
+        );
+        assert_eq!(cells[80].box_index, 8);
+        assert!(cells.iter().all(|cell| s[cell.pos] == cell.value));
+
+        // transpose the board
+        let mut transposed = SudokuBoard::default();
+        for cell in transposed.cells_mut() {
+            *cell.value = s[to_pos(cell.col, cell.row)];
+        }
+        assert_eq!(transposed[1], s[N2]);
+        assert_eq!(transposed.detect_symmetry(), s.detect_symmetry());
+    }
+
+    #[test]
+    #[cfg(feature = "html")]
+    fn to_html() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let html = s.to_html();
+        assert!(html.starts_with("<table class=\"sudoku\">\n<tr><td class=\"blank\"></td>"));
+        assert!(html.contains("<td class=\"given box-right\">2</td>"));
+        assert!(html.ends_with("<td class=\"given\">3</td></tr>\n</table>\n"));
+        assert_eq!(html.matches("<tr>").count(), N2);
+        assert_eq!(html.matches("class=\"given").count(), 25);
+        assert_eq!(html.matches("box-bottom").count(), N2 * 2);
+        assert!(!html.contains("style"));
+    }
+
+    #[test]
+    fn to_pencil_string() {
+        let mut s = SudokuBoard::default();
+        for (pos, value) in (0..N2 - 1).zip(1..) {
+            s[pos] = value;
+        }
+        let pencil = s.to_pencil_string();
+        let lines: Vec<Vec<char>> = pencil.lines().map(|line| line.chars().collect()).collect();
+        assert_eq!(lines.len(), 2 + N2 * N + N2 - 1);
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+
+        let cell = |line: usize, col: usize| -> String {
+            let start = 1 + col * (N + 1);
+            lines[line][start..start + N].iter().collect()
+        };
+        assert_eq!(cell(1, 0), "   ");
+        assert_eq!(cell(2, 0), "(1)");
+        // only a 9 fits at the end of the first row
+        assert_eq!(cell(1, 8), "   ");
+        assert_eq!(cell(3, 8), "  9");
+        // below the group of 1, 2 and 3
+        assert_eq!(cell(5, 0), "   ");
+        assert_eq!(cell(6, 0), "456");
+        assert_eq!(cell(7, 0), "789");
+        // the 8 is in the same column
+        assert_eq!(cell(lines.len() - 2, 7), "7 9");
+    }
+
+    #[test]
+    fn packed() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let packed = s.to_packed();
+        assert_eq!(packed.len(), 41);
+        assert_eq!(packed[0], 0x00);
+        assert_eq!(packed[1], 0x20);
+        assert_eq!(packed[40], 0x30);
+        assert_eq!(SudokuBoard::from_packed(&packed), Ok(s));
+
+        assert_eq!(
+            SudokuBoard::from_packed(&packed[1..]),
+            Err(ParseError::WrongLength {
+                expected: 41,
+                got: 40
+            })
+        );
+        let mut invalid = packed.clone();
+        invalid[1] = 0xa0;
+        assert_eq!(
+            SudokuBoard::from_packed(&invalid),
+            Err(ParseError::ValueOutOfRange { pos: 2, value: 10 })
+        );
+        let mut trailing = packed;
+        trailing[40] = 0x31;
+        assert_eq!(
+            SudokuBoard::from_packed(&trailing),
+            Err(ParseError::ValueOutOfRange { pos: 81, value: 1 })
+        );
+    }
+
+    #[test]
+    fn canonicalize() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let canonical = s.canonicalize();
+        assert!(canonical.to_line_string() <= s.to_line_string());
+        assert_eq!(canonical.clue_count(), s.clue_count());
+        assert_eq!(canonical.count_solutions(2), 1);
+        assert_eq!(canonical.canonicalize(), canonical);
+
+        let same = [
+            s.rotate_90(),
+            s.transpose(),
+            s.reflect_horizontal(),
+            s.relabel([9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            s.rotate_90().relabel([2, 3, 1, 5, 6, 4, 8, 9, 7]),
+        ];
+        for board in &same {
+            assert_eq!(board.canonicalize(), canonical);
+        }
+
+        let mut other = s.clone();
+        other[0] = 5;
+        assert_ne!(other.canonicalize(), canonical);
+
+        let solution = s.solve_all(1).pop().unwrap();
+        assert!(solution
+            .canonicalize()
+            .to_line_string()
+            .starts_with("123456789"));
+        assert_eq!(
+            SudokuBoard::default().canonicalize(),
+            SudokuBoard::default()
+        );
+    }
+
+    #[test]
+    fn sdk() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let sdk = s.to_sdk();
+        assert_eq!(sdk.lines().count(), N2);
+        assert!(sdk.starts_with("..2....3.\n....86.5.\n"));
+        assert_eq!(SudokuBoard::from_sdk(&sdk), Ok(s.clone()));
+
+        let with_metadata = format!(
+            "#AZheoni\n#DA unique puzzle\n#C 25 clues\n\n{}",
+            sdk.replace('.', "0")
+        );
+        assert_eq!(SudokuBoard::from_sdk(&with_metadata), Ok(s));
+
+        assert_eq!(
+            SudokuBoard::from_sdk("#A\n123\n456"),
+            Err(ParseError::WrongLength {
+                expected: SIZE,
+                got: 6
+            })
+        );
+    }
+
+    #[test]
+    fn as_slice() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert_eq!(s.as_slice().len(), SIZE);
+        assert_eq!(&s.as_slice()[..3], &[0, 0, 2]);
+        let values: &[u8; SIZE] = s.as_ref();
+        assert_eq!(values, &<[u8; SIZE]>::from(s.clone()));
+        let slice: &[u8] = s.as_ref();
+        assert_eq!(slice, s.as_slice());
+    }
+
+    #[test]
+    fn set_and_unset() {
+        let mut s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let original = s.clone();
+
+        assert!(s.set(SIZE, 1).is_err());
+        assert!(s.set(0, 0).is_err());
+        assert!(s.set(0, 42).is_err());
+        assert_eq!(s, original);
+
+        // 2 is already in the row, but set does not check it
+        assert!(s.place(0, 2).is_err());
+        assert_eq!(s, original);
+        assert!(s.set(0, 2).is_ok());
+        assert_eq!(s[0], 2);
+
+        s.unset(0);
+        assert_eq!(s, original);
+        assert!(s.place(0, 5).is_ok());
+        assert_eq!(s[0], 5);
+        // placing the same value again is still legal
+        assert!(s.place(0, 5).is_ok());
+        assert!(s.place(SIZE, 5).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "svg")]
+    fn to_svg() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let svg = s.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"360\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<line").count(), (N2 + 1) * 2);
+        assert_eq!(svg.matches("stroke-width=\"3\"").count(), (N + 1) * 2);
+        assert_eq!(svg.matches("class=\"given\"").count(), 25);
+        assert!(!svg.contains("candidate"));
+
+        let svg = s.to_svg_with(&SvgOptions {
+            cell_size: 90,
+            font: String::from("\"Fira Sans\""),
+            show_candidates: true,
+        });
+        assert!(svg.contains("width=\"810\""));
+        assert!(svg.contains("font-family=\"&quot;Fira Sans&quot;\""));
+        let candidates: usize = (0..SIZE).map(|pos| s.candidates(pos).len()).sum();
+        assert_eq!(svg.matches("class=\"candidate\"").count(), candidates);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data: Vec<u8> = (0..=255).collect();
+        let board = SudokuBoard::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert!(board.iter().all(|&value| value <= N2 as u8));
+        assert_eq!(
+            SudokuBoard::try_from(board.to_line_string().as_str()),
+            Ok(board)
+        );
+    }
+
+    #[test]
+    fn clue_impact() {
+        let mut s = SudokuBoard::default();
+        assert!(s.clue_impact().is_empty());
+
+        s[0] = 1;
+        s[1] = 2;
+        s[80] = 3;
+        assert_eq!(s.clue_impact(), vec![(0, 19), (1, 19), (80, 20)]);
+
+        let solved = SudokuBoard::generate_from_seed(&"IMPACT");
+        assert!(solved.clue_impact().iter().all(|&(_, impact)| impact == 0));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn solve_all_parallel() {
+        let s = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        assert_eq!(s.solve_all_parallel(10), s.solve_all(10));
+        assert_eq!(s.solve_all_parallel(1).len(), 1);
 
-impl Default for SudokuBoard {
-    fn default() -> Self {
-        SudokuBoard([0; SIZE])
+        let mut almost_empty = SudokuBoard::default();
+        almost_empty[0] = 1;
+        for max in [1, 7, 100] {
+            assert_eq!(
+                almost_empty.solve_all_parallel(max),
+                almost_empty.solve_all(max)
+            );
+        }
+
+        let solved = SudokuBoard::generate_from_seed(&"PARALLEL");
+        assert_eq!(solved.solve_all_parallel(5), vec![solved]);
     }
-}
 
-impl fmt::Display for SudokuBoard {
-    /// Pretty format for a sudoku
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if f.alternate() {
-            write!(f, "{}", self.to_line_string())
-        } else {
-            fn fmt_row(f: &mut fmt::Formatter, row: &[u8]) -> fmt::Result {
-                write!(f, "║")?;
-                for (i, &n) in row.iter().enumerate().take(N2) {
-                    if n != 0 {
-                        write!(f, "{: ^3}", n)?;
-                    } else {
-                        write!(f, "   ")?;
-                    }
-                    if i % N != N - 1 {
-                        write!(f, "│")?;
-                    } else {
-                        write!(f, "║")?;
-                    }
-                }
-                writeln!(f)
-            }
-            fn fmt_border(
-                f: &mut fmt::Formatter,
-                left: char,
-                num_sep: char,
-                group_sep: char,
-                right: char,
-                regular: char,
-            ) -> fmt::Result {
-                let num_border: String = std::iter::repeat(regular).take(3).collect();
-                write!(f, "{}", left)?;
-                for i in 0..N2 {
-                    write!(f, "{}", num_border)?;
-                    if i != N2 - 1 {
-                        if i % N != N - 1 {
-                            write!(f, "{}", num_sep)?;
-                        } else {
-                            write!(f, "{}", group_sep)?;
-                        }
-                    }
-                }
-                writeln!(f, "{}", right)
-            }
-            fmt_border(f, '╔', '═', '╦', '╗', '═')?;
-            for i in 0..N2 {
-                fmt_row(f, &self.0[i * N2..(i + 1) * N2])?;
-                if i != N2 - 1 {
-                    if i % N != N - 1 {
-                        fmt_border(f, '║', '┼', '║', '║', '─')?;
-                    } else {
-                        fmt_border(f, '╠', '═', '╬', '╣', '═')?;
-                    }
-                }
-            }
-            fmt_border(f, '╚', '═', '╩', '╝', '═')
+    #[test]
+    fn solve_variant() {
+        let diagonals = |board: &SudokuBoard| {
+            let mut main: Vec<u8> = (0..N2).map(|i| board[to_pos(i, i)]).collect();
+            let mut anti: Vec<u8> = (0..N2).map(|i| board[to_pos(i, N2 - 1 - i)]).collect();
+            main.sort_unstable();
+            anti.sort_unstable();
+            main == (1..=N2 as u8).collect::<Vec<_>>() && anti == main
+        };
+
+        let mut s = SudokuBoard::default();
+        assert!(s.solve_variant(Variant::Diagonal));
+        s.assert_valid_solution();
+        assert!(diagonals(&s));
+
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut classic = puzzle.clone();
+        assert!(classic.solve_variant(Variant::Classic));
+        assert!(!diagonals(&classic));
+        // its only solution breaks the diagonals
+        let mut diagonal = puzzle.clone();
+        assert!(!diagonal.solve_variant(Variant::Diagonal));
+        assert_eq!(diagonal, puzzle);
+
+        let mut repeated = SudokuBoard::default();
+        repeated[0] = 1;
+        repeated[80] = 1;
+        assert!(!repeated.solve_variant(Variant::Diagonal));
+        assert!(repeated.solve_variant(Variant::Classic));
+
+        let knight: fn(usize) -> Vec<usize> = |pos| knight_positions(pos).collect();
+        let king: fn(usize) -> Vec<usize> = |pos| king_positions(pos).collect();
+        for &(variant, moves) in &[(Variant::AntiKnight, knight), (Variant::AntiKing, king)] {
+            let mut s = SudokuBoard::default();
+            assert!(s.solve_variant(variant));
+            s.assert_valid_solution();
+            assert!((0..SIZE).all(|pos| moves(pos).iter().all(|&p| s[p] != s[pos])));
+
+            let mut repeated = SudokuBoard::default();
+            repeated[to_pos(1, 3)] = 1;
+            let p = moves(to_pos(1, 3))
+                .into_iter()
+                .find(|&p| !is_peer(p, to_pos(1, 3)))
+                .unwrap();
+            repeated[p] = 1;
+            assert!(!repeated.is_valid_variant(to_pos(1, 3), 1, variant));
+            assert!(repeated.is_valid_variant(to_pos(1, 3), 1, Variant::Classic));
+            assert!(!repeated.solve_variant(variant));
         }
+
+        let mut s = SudokuBoard::default();
+        assert!(s.solve_variant(Variant::NonConsecutive));
+        s.assert_valid_solution();
+        assert!(
+            (0..SIZE).all(|pos| orthogonal_positions(pos).all(|p| !are_consecutive(s[p], s[pos])))
+        );
+
+        let mut consecutive = SudokuBoard::default();
+        consecutive[to_pos(4, 4)] = 5;
+        assert!(!consecutive.is_valid_variant(to_pos(5, 4), 4, Variant::NonConsecutive));
+        assert!(consecutive.is_valid_variant(to_pos(5, 4), 4, Variant::Classic));
+        assert!(consecutive.is_valid_variant(to_pos(5, 5), 4, Variant::NonConsecutive));
+        consecutive[to_pos(5, 4)] = 4;
+        assert!(!consecutive.solve_variant(Variant::NonConsecutive));
+        assert!(consecutive.solve_variant(Variant::Classic));
     }
-}
 
-impl TryFrom<&str> for SudokuBoard {
-    type Error = &'static str;
+    #[test]
+    fn solve_with_stats() {
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
 
-    /// Tries to converts a sudoku board string representation into a sudoku board.
-    ///
-    /// **May not work correctly for SIZE > 3** because in one char does not fit the value
-    fn try_from(s: &str) -> Result<SudokuBoard, Self::Error> {
-        if s.len() != SIZE {
-            return Err("Invalid str len, must be SIZE");
+        let mut s = puzzle.clone();
+        let (solved, stats) = s.solve_with_stats();
+        assert!(solved);
+        assert_eq!(
+            s.to_line_string(),
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+        );
+        assert!(stats.max_depth <= puzzle.empty_count());
+        assert!(stats.nodes_visited > stats.max_depth);
+        assert!(stats.guesses > 0);
+
+        // the counters start again in every call
+        let mut again = puzzle.clone();
+        assert_eq!(again.solve_with_stats().1, stats);
+
+        // a solved board only visits itself
+        let (solved, stats) = s.solve_with_stats();
+        assert!(solved);
+        assert_eq!(
+            stats,
+            SolveStats {
+                nodes_visited: 1,
+                ..SolveStats::default()
+            }
+        );
+
+        let mut s = SudokuBoard::try_from(
+            "12345678.........9...............................................................",
+        )
+        .unwrap();
+        let (solved, stats) = s.solve_with_stats();
+        assert!(!solved);
+        assert_eq!(stats.guesses, 0);
+        assert_eq!(stats.max_depth, 1);
+    }
+
+    #[test]
+    fn solve_with_config() {
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+
+        let mut s = puzzle.clone();
+        assert_eq!(
+            s.solve_with_config(&SolverConfig::default()),
+            SolveOutcome::Solved
+        );
+        assert_eq!(
+            s.to_line_string(),
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+        );
+
+        let mut s = puzzle.clone();
+        assert_eq!(
+            s.solve_with_config(&SolverConfig { max_depth: 10 }),
+            SolveOutcome::DepthLimitExceeded
+        );
+        assert_eq!(s, puzzle);
+
+        let mut s = SudokuBoard::try_from(
+            "12345678.........9...............................................................",
+        )
+        .unwrap();
+        assert_eq!(
+            s.solve_with_config(&SolverConfig::default()),
+            SolveOutcome::Unsolvable
+        );
+    }
+
+    #[test]
+    fn solve_seeded() {
+        let mut a = SudokuBoard::default();
+        let mut b = SudokuBoard::default();
+        assert!(a.solve_seeded("SEED"));
+        assert!(b.solve_seeded("SEED"));
+        assert_eq!(a, b);
+        assert_eq!(a.count_solutions(2), 1);
+        for pos in 0..SIZE {
+            let value = a[pos];
+            a[pos] = 0;
+            assert!(a.is_valid(pos, value));
+            a[pos] = value;
         }
 
-        s.chars()
-            .map(|c| match c {
-                '.' => Ok(0),
-                c => c.to_digit(10).ok_or("Invalid character").map(|d| d as u8),
-            })
-            .collect::<Result<Vec<u8>, Self::Error>>()
-            .and_then(|v| v.try_into())
+        let mut c = SudokuBoard::default();
+        assert!(c.solve_seeded("OTHER"));
+        assert_ne!(a, c);
+
+        let puzzle = SudokuBoard::try_from(
+            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+        )
+        .unwrap();
+        let solutions = puzzle.solve_all(2);
+        for seed in &["A", "B", "C", "D"] {
+            let mut s = puzzle.clone();
+            assert!(s.solve_seeded(seed));
+            assert!(solutions.contains(&s));
+        }
     }
-}
 
-impl TryFrom<[u8; SIZE]> for SudokuBoard {
-    type Error = &'static str;
+    #[test]
+    fn solve_best_effort() {
+        let puzzle = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut solution = puzzle.clone();
+        solution.solve();
 
-    fn try_from(arr: [u8; SIZE]) -> Result<SudokuBoard, Self::Error> {
-        if arr.iter().all(|&d| d <= N2 as u8) {
-            Ok(SudokuBoard(arr))
-        } else {
-            Err("Values must be between 0 and sqrt(SIZE)")
+        assert!(puzzle.find_conflicts().is_empty());
+        assert_eq!(puzzle.solve_best_effort(), (solution.clone(), vec![]));
+
+        let mut typo = puzzle.clone();
+        typo[0] = 2;
+        assert_eq!(typo.find_conflicts(), vec![0, 2]);
+        assert_eq!(typo.solve_best_effort(), (solution, vec![0]));
+
+        let mut combination = vec![0, 1];
+        let mut count = 1;
+        while next_combination(&mut combination, 4) {
+            count += 1;
         }
+        assert_eq!(count, 6);
+        assert_eq!(combination, vec![2, 3]);
     }
-}
 
-impl TryFrom<Vec<u8>> for SudokuBoard {
-    type Error = &'static str;
-    fn try_from(vec: Vec<u8>) -> Result<SudokuBoard, Self::Error> {
-        vec.as_slice().try_into()
+    #[test]
+    fn into_conversions() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+
+        let line = String::from(&s);
+        assert_eq!(SudokuBoard::try_from(line.as_str()), Ok(s.clone()));
+
+        let vec: Vec<u8> = s.clone().into();
+        assert_eq!(SudokuBoard::try_from(vec), Ok(s.clone()));
+
+        let arr: [u8; SIZE] = s.clone().into();
+        assert_eq!(SudokuBoard::try_from(arr), Ok(s));
     }
-}
 
-impl TryFrom<&[u8]> for SudokuBoard {
-    type Error = &'static str;
-    fn try_from(slice: &[u8]) -> Result<SudokuBoard, Self::Error> {
-        <[u8; SIZE]>::try_from(slice)
-            .map_err(|_| "Could not convert slice to array")
-            .and_then(SudokuBoard::try_from)
+    #[test]
+    fn generate_with_diversity() {
+        for &substitutions in &[0, 5, 40, SIZE] {
+            let mut rng: Pcg64 = Seeder::from("DIVERSITY").make_rng();
+            let board = SudokuBoard::generate_with_diversity(&mut rng, substitutions);
+            assert!(board.iter().all(|&value| value != 0));
+            assert!(board.find_conflicts().is_empty());
+        }
+
+        let mut rng: Pcg64 = Seeder::from("DIVERSITY").make_rng();
+        let a = SudokuBoard::generate_with_diversity(&mut rng, 15);
+        let mut rng: Pcg64 = Seeder::from("DIVERSITY").make_rng();
+        let b = SudokuBoard::generate_with_diversity(&mut rng, 15);
+        assert_eq!(a, b);
     }
-}
 
-impl std::ops::Deref for SudokuBoard {
-    type Target = [u8; SIZE];
+    #[test]
+    fn assert_valid_solution() {
+        SudokuBoard::generate_from_seed(&"VALID").assert_valid_solution();
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    #[test]
+    #[should_panic(expected = "Row 0 has an empty or invalid position")]
+    fn assert_valid_solution_empty() {
+        let mut s = SudokuBoard::generate_from_seed(&"VALID");
+        s[3] = 0;
+        s.assert_valid_solution();
     }
-}
 
-impl std::ops::DerefMut for SudokuBoard {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    #[test]
+    #[should_panic(expected = "Column 1 has the value")]
+    fn assert_valid_solution_repeated() {
+        let mut s = SudokuBoard::generate_from_seed(&"VALID");
+        s.0.swap(1, 2);
+        s.0.swap(N2 + 1, N2 + 2);
+        s.0.swap(N2 * 2 + 1, N2 * 2 + 2);
+        s.assert_valid_solution();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
     #[test]
-    fn line_string() {
+    fn hex_symbols() {
+        for value in 0..=16 {
+            let c = symbol(value, 16);
+            assert_eq!(symbol_value(c, 16), Some(value));
+            assert_eq!(symbol_value(c.to_ascii_lowercase(), 16), Some(value));
+        }
+        assert_eq!(symbol(1, 16), '0');
+        assert_eq!(symbol(10, 16), '9');
+        assert_eq!(symbol(16, 16), 'F');
+        assert_eq!(symbol_value('G', 16), None);
+
+        assert_eq!(symbol(9, 9), '9');
+        assert_eq!(symbol_value('0', 9), Some(0));
+        assert_eq!(symbol_value('A', 9), None);
+        assert_eq!(symbol_value('9', 4), None);
+    }
+
+    #[test]
+    fn candidates() {
         let s = SudokuBoard::try_from(
             "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        assert_eq!(
-            s.to_line_string(),
-            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3"
-        )
+        let domains = Domains::calculate_domains(&s);
+        for pos in 0..SIZE {
+            let expected: Vec<u8> = if s[pos] == 0 {
+                domains.values(pos).collect()
+            } else {
+                Vec::new()
+            };
+            assert_eq!(s.candidates(pos), expected);
+        }
+        assert_eq!(s.candidates(0), vec![1, 4, 5, 7, 8, 9]);
+        assert!(s.candidates(2).is_empty());
     }
 
     #[test]
-    fn line_string_zeroes() {
+    fn candidates_mask() {
         let s = SudokuBoard::try_from(
-            "002....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        assert_eq!(
-            s.to_line_string(),
-            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3"
-        )
+        let mask = s.candidates_mask();
+        for (pos, &m) in mask.iter().enumerate() {
+            let values: Vec<u8> = (1..=N2 as u8).filter(|v| m & (1 << (v - 1)) != 0).collect();
+            assert_eq!(values, s.candidates(pos));
+        }
+        assert_eq!(mask[0], 0b1_1101_1001);
+        assert_eq!(mask[2], 0);
     }
 
     #[test]
-    fn solve_1() {
+    fn conflicts() {
         let mut s = SudokuBoard::try_from(
             "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        s.solve();
-        assert_eq!(
-            s.to_line_string(),
-            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
-        );
+        assert!(s.is_consistent());
+        assert!(s.conflicts().is_empty());
+
+        s[0] = 2;
+        s[80] = 5;
+        assert!(!s.is_consistent());
+        assert_eq!(s.conflicts(), vec![(0, 2), (78, 80)]);
     }
 
     #[test]
-    fn solve_all_1() {
-        let s = SudokuBoard::try_from(
+    fn symmetry_score() {
+        let mut s = SudokuBoard::default();
+        assert_eq!(s.symmetry_score(), 1.0);
+        s[0] = 1;
+        assert_eq!(s.symmetry_score(), 0.0);
+        s[80] = 2;
+        assert_eq!(s.symmetry_score(), 1.0);
+        s[40] = 3;
+        assert_eq!(s.symmetry_score(), 1.0);
+        s[1] = 4;
+        assert_eq!(s.symmetry_score(), 0.75);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let line =
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3";
+        let s = SudokuBoard::try_from(line).unwrap();
+
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, format!("\"{}\"", line));
+        assert_eq!(serde_json::from_str::<SudokuBoard>(&json).unwrap(), s);
+
+        assert!(serde_json::from_str::<SudokuBoard>("\"123\"").is_err());
+        assert!(serde_json::from_str::<SudokuBoard>("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn exchange() {
+        let puzzle = SudokuBoard::try_from(
             "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        let solutions = s.solve_all(10);
-        assert_eq!(solutions.len(), 1);
+        let mut solution = puzzle.clone();
+        solution.solve();
+
+        let line = puzzle.to_exchange("1", Some(&solution));
         assert_eq!(
-            solutions[0].to_line_string(),
-            "542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+            line,
+            "1,002000030000086050036500091000000006069100070008000000000000900000008017071600503,\
+             542971638917386254836542791723859146469123875158467329384715962695238417271694583"
+        );
+        let expected = (puzzle.clone(), Some(solution.clone()));
+        assert_eq!(SudokuBoard::from_exchange(&line), Ok(expected.clone()));
+
+        let kaggle = line.trim_start_matches("1,");
+        assert_eq!(SudokuBoard::from_exchange(kaggle), Ok(expected));
+
+        let clue_count = format!("25 {}", puzzle.to_line_string());
+        assert_eq!(
+            SudokuBoard::from_exchange(&clue_count),
+            Ok((puzzle.clone(), None))
+        );
+        assert_eq!(
+            SudokuBoard::from_exchange(&puzzle.to_exchange("id", None)),
+            Ok((puzzle, None))
+        );
+
+        assert_eq!(
+            SudokuBoard::from_exchange("quizzes,solutions"),
+            Err(ParseError::WrongLength {
+                expected: SIZE,
+                got: 9
+            })
         );
     }
 
     #[test]
-    fn count_1() {
+    fn is_minimal() {
         let s = SudokuBoard::try_from(
             "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        assert_eq!(s.count_solutions(10), 1);
+        let mut solution = s.clone();
+        solution.solve();
+        assert!(!solution.is_minimal());
+        assert!(!SudokuBoard::default().is_minimal());
+
+        // a value of the solution makes it redundant
+        let mut redundant = s.clone();
+        let pos = (0..SIZE).find(|&pos| s[pos] == 0).unwrap();
+        redundant[pos] = solution[pos];
+        assert!(!redundant.is_minimal());
     }
 
     #[test]
-    fn solve_all_more() {
-        let s = SudokuBoard::try_from(
-            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+    fn is_solved() {
+        let solved = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
         )
         .unwrap();
-        let solutions = s.solve_all(10);
-        assert_eq!(solutions.len(), 2);
-        let solutions = solutions
-            .iter()
-            .map(|s| s.to_line_string())
-            .collect::<Vec<_>>();
-        assert!(solutions.contains(
-            &"926571483351486279874923516582367194149258267763194825238749651617835942495612738"
-                .to_string()
-        ));
-        assert!(solutions.contains(
-            &"926571483351486279874923516582367194149258267763149825238794651617835942495612738"
-                .to_string()
-        ));
+        assert!(solved.is_solved());
+        assert!(!SudokuBoard::default().is_solved());
+
+        let mut incomplete = solved.clone();
+        incomplete[40] = 0;
+        assert!(incomplete.is_consistent());
+        assert!(!incomplete.is_solved());
+
+        // complete but with repeated values
+        let mut swapped = solved.clone();
+        swapped.swap(0, 1);
+        assert!(!swapped.is_solved());
+        assert!(!SudokuBoard::try_from([1; SIZE]).unwrap().is_solved());
     }
 
     #[test]
-    fn count_more() {
+    fn clue_count() {
         let s = SudokuBoard::try_from(
-            "9265714833514862798749235165823671941492582677631..8252387..651617835942495612738",
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
         )
         .unwrap();
-        assert_eq!(s.count_solutions(10), 2);
+        assert_eq!(s.clue_count(), 25);
+        assert_eq!(s.empty_count(), SIZE - 25);
+        assert_eq!(SudokuBoard::default().clue_count(), 0);
+        assert_eq!(SudokuBoard::default().empty_count(), SIZE);
     }
 
     #[test]
-    fn generate() {
-        use rand::SeedableRng;
-        use rand_pcg::Pcg64;
-        let s = SudokuBoard::generate(&mut Pcg64::from_entropy());
-        for (pos, &val) in s.0.iter().enumerate() {
-            assert_ne!(val, 0);
-            assert!(s.is_valid(pos, val));
+    fn grid() {
+        let grid = [
+            [5, 4, 2, 9, 7, 1, 6, 3, 8],
+            [9, 1, 7, 3, 8, 6, 2, 5, 4],
+            [8, 3, 6, 5, 4, 2, 7, 9, 1],
+            [7, 2, 3, 8, 5, 9, 1, 4, 6],
+            [4, 6, 9, 1, 2, 3, 8, 7, 5],
+            [1, 5, 8, 4, 6, 7, 3, 2, 9],
+            [3, 8, 4, 7, 1, 5, 9, 6, 2],
+            [6, 9, 5, 2, 3, 8, 4, 1, 7],
+            [2, 7, 1, 6, 9, 4, 5, 8, 0],
+        ];
+        let s = SudokuBoard::try_from(grid).unwrap();
+        assert_eq!(
+            s,
+            SudokuBoard::try_from(
+                "54297163891738625483654279172385914646912387515846732938471596269523841727169458."
+            )
+            .unwrap()
+        );
+        assert_eq!(s.to_grid(), grid);
+
+        let mut grid = grid;
+        grid[1][2] = 10;
+        assert_eq!(
+            SudokuBoard::try_from(grid),
+            Err(ParseError::ValueOutOfRange {
+                pos: N2 + 2,
+                value: 10
+            })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            SudokuBoard::try_from("123"),
+            Err(ParseError::WrongLength {
+                expected: SIZE,
+                got: 3
+            })
+        );
+        let mut line = ".".repeat(SIZE);
+        line.replace_range(5..6, "x");
+        assert_eq!(
+            SudokuBoard::try_from(line.as_str()),
+            Err(ParseError::InvalidCharacter { pos: 5, ch: 'x' })
+        );
+        let mut line = ".".repeat(SIZE - 1);
+        line.insert(3, 'ñ');
+        assert_eq!(
+            SudokuBoard::try_from(line.as_str()),
+            Err(ParseError::InvalidCharacter { pos: 3, ch: 'ñ' })
+        );
+
+        let mut values = [0; SIZE];
+        values[7] = 10;
+        assert_eq!(
+            SudokuBoard::try_from(values),
+            Err(ParseError::ValueOutOfRange { pos: 7, value: 10 })
+        );
+        assert_eq!(
+            SudokuBoard::try_from(vec![1, 2]),
+            Err(ParseError::WrongLength {
+                expected: SIZE,
+                got: 2
+            })
+        );
+    }
+
+    #[test]
+    fn with_diagonal_boxes() {
+        let mut rng: Pcg64 = Seeder::from("DIAGONAL").make_rng();
+        let board = SudokuBoard::with_diagonal_boxes(&mut rng);
+
+        assert!(board.is_consistent());
+        assert_eq!(board.iter().filter(|&&value| value != 0).count(), N2 * N);
+        for (index, mut values) in board.boxes().enumerate() {
+            if index % (N + 1) == 0 {
+                values.sort_unstable();
+                assert_eq!(values, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+            } else {
+                assert_eq!(values, [0; N2]);
+            }
         }
+        assert_eq!(board.count_solutions(1), 1);
     }
 }
@@ -0,0 +1,228 @@
+//! Killer sudokus: besides the usual rules, the board is divided in cages
+//! whose values must add up to the sum of the cage without repeating any.
+
+use crate::alloc_prelude::*;
+use crate::board::{Domains, SudokuBoard};
+use crate::{N2, SIZE};
+
+/// Positions of a killer sudoku whose values add up to `sum`. A value can
+/// not be repeated inside a cage, even if the positions are in different
+/// rows, columns and groups.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cage {
+    /// Positions of the cage
+    pub cells: Vec<usize>,
+    /// Sum of the values of the cage
+    pub sum: u8,
+}
+
+impl Cage {
+    /// Creates a cage with its positions and its sum
+    pub fn new(cells: Vec<usize>, sum: u8) -> Self {
+        Self { cells, sum }
+    }
+
+    /// Checks that the cages cover every position of the board exactly once,
+    /// like in a killer sudoku, and that the sum of each cage can be made
+    /// with as many different values as positions it has.
+    pub fn validate_partition(cages: &[Cage]) -> Result<(), &'static str> {
+        let mut covered = [false; SIZE];
+        for cage in cages {
+            if cage.cells.is_empty() || cage.cells.len() > N2 {
+                return Err("Cage with an invalid number of positions");
+            }
+            let (min, max) = sum_bounds(cage.cells.len(), 0);
+            if !(min..=max).contains(&(cage.sum as usize)) {
+                return Err("Cage sum can not be reached");
+            }
+            for &pos in &cage.cells {
+                if pos >= SIZE {
+                    return Err("Position out of the board");
+                }
+                if covered[pos] {
+                    return Err("Position in more than one cage");
+                }
+                covered[pos] = true;
+            }
+        }
+        if covered.contains(&false) {
+            return Err("Position not in any cage");
+        }
+        Ok(())
+    }
+
+    /// Checks if the values of the cage in `board` can still add up to the
+    /// sum: no value is repeated, and the empty positions can be filled with
+    /// the values not used yet.
+    fn is_possible(&self, board: &SudokuBoard) -> bool {
+        let mut used = 0u16;
+        let mut total = 0;
+        let mut empty = 0;
+        for &pos in &self.cells {
+            let value = board[pos];
+            if value == 0 {
+                empty += 1;
+                continue;
+            }
+            let bit = 1 << (value - 1);
+            if used & bit != 0 {
+                return false;
+            }
+            used |= bit;
+            total += value as usize;
+        }
+
+        let sum = self.sum as usize;
+        if total > sum {
+            return false;
+        }
+        let (min, max) = sum_bounds(empty, used);
+        (min..=max).contains(&(sum - total))
+    }
+}
+
+/// Minimum and maximum sums of `count` different values, without the ones
+/// in the bitmask `used`. If there are not enough values, no sum is possible
+/// and the minimum is over the maximum.
+fn sum_bounds(count: usize, used: u16) -> (usize, usize) {
+    let free: Vec<usize> = (1..=N2).filter(|v| used & (1 << (v - 1)) == 0).collect();
+    if count > free.len() {
+        return (1, 0);
+    }
+    (
+        free[..count].iter().sum(),
+        free[free.len() - count..].iter().sum(),
+    )
+}
+
+impl Domains {
+    /// Checks if all the cages with `pos` can still add up to their sum.
+    pub(crate) fn cages_possible(&self, board: &SudokuBoard, pos: usize) -> bool {
+        self.cages
+            .iter()
+            .filter(|cage| cage.cells.contains(&pos))
+            .all(|cage| cage.is_possible(board))
+    }
+}
+
+impl SudokuBoard {
+    /// Solves the killer sudoku in place like [SudokuBoard::solve], with the
+    /// sums of the `cages` on top of the usual rules. The cages do not need
+    /// to cover the whole board, see [Cage::validate_partition] to check it.
+    ///
+    /// Returns false if it has no solution, also if a cage has a position
+    /// out of the board or the givens already break a cage.
+    pub fn solve_killer(&mut self, cages: &[Cage]) -> bool {
+        if cages
+            .iter()
+            .any(|cage| cage.cells.iter().any(|&pos| pos >= SIZE) || !cage.is_possible(self))
+        {
+            return false;
+        }
+
+        let mut domains = Domains::calculate_domains(self);
+        // remove the values that can not add up to the sum of their cage
+        for cage in cages {
+            for &pos in &cage.cells {
+                if self[pos] != 0 {
+                    continue;
+                }
+                let values: Vec<u8> = domains.values(pos).collect();
+                for value in values {
+                    self[pos] = value;
+                    if !cage.is_possible(self) {
+                        domains.remove(pos, value);
+                    }
+                }
+                self[pos] = 0;
+            }
+        }
+        domains.cages = cages.to_vec();
+
+        self.solve_from_domains(domains)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    /// Cages of two positions side by side in each row, and the last column
+    /// alone, with the sums of `solution`.
+    fn domino_cages(solution: &SudokuBoard) -> Vec<Cage> {
+        (0..SIZE)
+            .step_by(N2)
+            .flat_map(|start| {
+                (start..start + N2)
+                    .step_by(2)
+                    .map(move |pos| (pos..(pos + 2).min(start + N2)).collect::<Vec<_>>())
+            })
+            .map(|cells| {
+                let sum = cells.iter().map(|&p| solution[p]).sum();
+                Cage::new(cells, sum)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn solve_killer() {
+        let solution = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        let cages = domino_cages(&solution);
+        assert_eq!(Cage::validate_partition(&cages), Ok(()));
+
+        let mut s = SudokuBoard::default();
+        assert!(s.solve_killer(&cages));
+        s.assert_valid_solution();
+        for cage in &cages {
+            assert!(cage.is_possible(&s));
+        }
+
+        let mut impossible = SudokuBoard::default();
+        assert!(!impossible.solve_killer(&[Cage::new(vec![0, 1], 2)]));
+        assert_eq!(impossible, SudokuBoard::default());
+
+        let mut given = SudokuBoard::default();
+        given[0] = 9;
+        assert!(!given.solve_killer(&[Cage::new(vec![0, 10], 5)]));
+        assert!(!given.solve_killer(&[Cage::new(vec![SIZE], 5)]));
+        // not repeated in the cage, even if not in the same unit
+        assert!(given.solve_killer(&[Cage::new(vec![0, 80], 17)]));
+        assert_eq!(given[80], 8);
+    }
+
+    #[test]
+    fn validate_partition() {
+        let solution = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        let mut cages = domino_cages(&solution);
+        cages.pop();
+        assert_eq!(
+            Cage::validate_partition(&cages),
+            Err("Position not in any cage")
+        );
+        cages.push(Cage::new(vec![79, 80], 3));
+        assert_eq!(
+            Cage::validate_partition(&cages),
+            Err("Position in more than one cage")
+        );
+        cages.pop();
+        cages.push(Cage::new(vec![80, 81], 3));
+        assert_eq!(
+            Cage::validate_partition(&cages),
+            Err("Position out of the board")
+        );
+        cages.pop();
+        cages.push(Cage::new(vec![80], 10));
+        assert_eq!(
+            Cage::validate_partition(&cages),
+            Err("Cage sum can not be reached")
+        );
+    }
+}
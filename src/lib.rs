@@ -1,10 +1,13 @@
 #![deny(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! # Sudoku
 //! Library to solve and generate sudokus.
 //!
 //! - For solving sudokus: [board::SudokuBoard]
 //! - For generating sudokus: [puzzle::SudokuPuzzle]
+//! - For solving sudokus like a human would: [logic]
+//! - For solving killer sudokus: [killer]
 //!
 //! # Quick start
 //! ## Solving a sodoku
@@ -31,8 +34,33 @@
 //!     .generate();
 //! println!("{}", puzzle);
 //! ```
+//!
+//! # Board size
+//! Boards are always 9x9, with groups of 3x3. The size is a constant of the
+//! crate instead of a const generic parameter of [board::SudokuBoard],
+//! because the board is stored in an array of `N^4` values, and array
+//! lengths computed from a const parameter need `generic_const_exprs`, which
+//! is not available in stable Rust. The parsing and printing already use the
+//! hexadoku notation for boards bigger than 9x9.
+//!
+//! # `no_std`
+//! Without the default `std` feature, the crate only needs `alloc`. Solving,
+//! counting and grading work the same, but the generation can not measure
+//! its time nor make up a random seed, see [puzzle::Generator::generate].
+
+extern crate alloc;
+
+/// Items of `alloc` that are in the `std` prelude, so the modules use them
+/// the same way with or without the `std` feature.
+mod alloc_prelude {
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec::Vec;
+    pub use alloc::{format, vec};
+}
 
 pub mod board;
+pub mod killer;
+pub mod logic;
 mod pos_util;
 pub mod prelude;
 pub mod puzzle;
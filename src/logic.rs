@@ -0,0 +1,1037 @@
+//! Logical solving of a sudoku: applies the techniques a human would use
+//! instead of guessing, and grades the difficulty of a board with them.
+
+use crate::alloc_prelude::*;
+use crate::board::{Domains, SudokuBoard, UnitKind};
+use crate::pos_util::*;
+use crate::puzzle::Difficulty;
+use crate::{N2, SIZE};
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// Techniques known by the logical solver, from the easiest to the hardest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    /// An empty position with only one possible value
+    NakedSingle,
+    /// A value that only fits in one position of a row, column or group
+    HiddenSingle,
+    /// The positions of a value in a unit are all in another unit too, like
+    /// a group and a row, so the value is removed from the rest of the
+    /// other unit
+    LockedCandidates,
+    /// Two positions of a unit with the same two possible values, which are
+    /// removed from the rest of the unit
+    NakedPair,
+    /// Two values that only fit in the same two positions of a unit, so the
+    /// rest of values are removed from those positions
+    HiddenPair,
+}
+
+impl Technique {
+    /// Returns all the techniques, from the easiest to the hardest
+    pub const fn all() -> &'static [Technique; 5] {
+        &[
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+            Technique::LockedCandidates,
+            Technique::NakedPair,
+            Technique::HiddenPair,
+        ]
+    }
+
+    /// Returns the identifiers of all the techniques, from the easiest to
+    /// the hardest. These are the values accepted by `try_from`.
+    pub const fn get_all_ids() -> &'static [&'static str; 5] {
+        &[
+            "nakedsingle",
+            "hiddensingle",
+            "lockedcandidates",
+            "nakedpair",
+            "hiddenpair",
+        ]
+    }
+
+    /// Returns the str representation of the technique
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::LockedCandidates => "locked candidates",
+            Technique::NakedPair => "naked pair",
+            Technique::HiddenPair => "hidden pair",
+        }
+    }
+
+    /// Weight of each use of the technique in
+    /// [SudokuBoard::difficulty_score], inspired by the ratings of Sudoku
+    /// Explainer: harder techniques weigh more.
+    pub const fn weight(&self) -> f64 {
+        match self {
+            Technique::NakedSingle => 1.0,
+            Technique::HiddenSingle => 1.5,
+            Technique::LockedCandidates => 2.6,
+            Technique::NakedPair => 3.0,
+            Technique::HiddenPair => 3.4,
+        }
+    }
+}
+
+/// Weight in [SudokuBoard::difficulty_score] of each position left empty
+/// when the techniques are not enough, heavier than any technique.
+pub const GUESS_WEIGHT: f64 = 5.0;
+
+impl TryFrom<&str> for Technique {
+    type Error = &'static str;
+    fn try_from(val: &str) -> Result<Self, Self::Error> {
+        match val {
+            "nakedsingle" => Ok(Self::NakedSingle),
+            "hiddensingle" => Ok(Self::HiddenSingle),
+            "lockedcandidates" => Ok(Self::LockedCandidates),
+            "nakedpair" => Ok(Self::NakedPair),
+            "hiddenpair" => Ok(Self::HiddenPair),
+            _ => Err("Unknown technique"),
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Outcome of [SudokuBoard::solve_until_guess]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveUntilGuess {
+    /// The board was solved without guessing
+    Solved,
+    /// The techniques are not enough, a value has to be guessed. `pos` is
+    /// the empty position with the fewest possible values, `candidates`.
+    Guess {
+        /// Position to guess
+        pos: usize,
+        /// Possible values of the position
+        candidates: Vec<u8>,
+    },
+    /// The empty position `pos` has no possible value, so the board has no
+    /// solution
+    Contradiction {
+        /// Position without possible values
+        pos: usize,
+    },
+}
+
+/// Result of [SudokuBoard::grade_with]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grade {
+    /// Difficulty of the board
+    pub difficulty: Difficulty,
+    /// Hardest technique used, `None` if nothing could be placed
+    pub hardest_technique: Option<Technique>,
+    /// If the techniques are not enough and some value has to be guessed
+    pub requires_guessing: bool,
+}
+
+/// A value placed by the logical solver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolveStep {
+    /// Position of the value
+    pub pos: usize,
+    /// Value placed
+    pub value: u8,
+    /// Technique that found the value
+    pub technique: Technique,
+}
+
+/// Possible values removed by a technique that does not place a value, like
+/// "since 3 and 7 are locked to these two positions of the group, remove them
+/// from the rest of the row".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Elimination {
+    /// Technique that found the elimination
+    pub technique: Technique,
+    /// Positions of the pattern that justifies it
+    pub cells: Vec<usize>,
+    /// Values of the pattern
+    pub values: Vec<u8>,
+    /// Unit where the values are removed, with its kind and its index from
+    /// 0 to N2 - 1
+    pub unit: (UnitKind, usize),
+    /// Pairs of position and value removed from its possible values
+    pub removed: Vec<(usize, u8)>,
+}
+
+/// Next value a player can deduce, see [SudokuBoard::hint]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    /// Position of the value
+    pub pos: usize,
+    /// Value to place
+    pub value: u8,
+    /// Hardest technique needed to deduce the value
+    pub technique: Technique,
+    /// Positions that justify the value: the ones with values that rule out
+    /// the rest, and the ones of the patterns of the eliminations needed
+    pub cells: Vec<usize>,
+    /// Eliminations needed before the value can be placed, in order
+    pub eliminations: Vec<Elimination>,
+}
+
+/// Result of solving a board only with the techniques.
+pub(crate) struct LogicalSolve {
+    /// Placements in the order they were found
+    pub steps: Vec<SolveStep>,
+    /// Eliminations in the order they were applied
+    pub eliminations: Vec<Elimination>,
+    /// If the board was completed
+    pub solved: bool,
+    /// Empty positions left when the techniques were not enough
+    pub remaining: usize,
+}
+
+impl LogicalSolve {
+    /// Techniques used, from the easiest to the hardest
+    pub fn techniques(&self) -> Vec<Technique> {
+        let mut techniques: Vec<Technique> = self
+            .steps
+            .iter()
+            .map(|step| step.technique)
+            .chain(self.eliminations.iter().map(|e| e.technique))
+            .collect();
+        techniques.sort_unstable();
+        techniques.dedup();
+        techniques
+    }
+
+    /// Score of the solve, see [SudokuBoard::difficulty_score]
+    pub fn score(&self) -> f64 {
+        let techniques: f64 = self
+            .steps
+            .iter()
+            .map(|step| step.technique.weight())
+            .chain(self.eliminations.iter().map(|e| e.technique.weight()))
+            .sum();
+        techniques + self.remaining as f64 * GUESS_WEIGHT
+    }
+}
+
+/// Finds all the placements that a technique can make on the board.
+fn placements(board: &SudokuBoard, domains: &Domains, technique: Technique) -> Vec<(usize, u8)> {
+    let mut found = Vec::new();
+    match technique {
+        Technique::NakedSingle => {
+            for pos in (0..SIZE).filter(|&pos| board[pos] == 0) {
+                let mut values = domains.values(pos);
+                if let (Some(value), None) = (values.next(), values.next()) {
+                    found.push((pos, value));
+                }
+            }
+        }
+        Technique::HiddenSingle => {
+            for (pos, value, _) in hidden_singles(board, domains) {
+                // the same placement can be hidden in more than one unit
+                if !found.contains(&(pos, value)) {
+                    found.push((pos, value));
+                }
+            }
+        }
+        Technique::LockedCandidates | Technique::NakedPair | Technique::HiddenPair => {}
+    }
+    found
+}
+
+/// Finds the values that only fit in one position of a unit, with that
+/// position and the unit, from 0 to [UNITS] - 1.
+fn hidden_singles(board: &SudokuBoard, domains: &Domains) -> Vec<(usize, u8, usize)> {
+    let mut found = Vec::new();
+    for unit in 0..UNITS {
+        let positions = unit_positions(unit);
+        for value in 1..=N2 as u8 {
+            let mut fits = positions
+                .iter()
+                .copied()
+                .filter(|&pos| board[pos] == 0 && domains.contains(pos, value));
+            if let (Some(pos), None) = (fits.next(), fits.next()) {
+                found.push((pos, value, unit));
+            }
+        }
+    }
+    found
+}
+
+/// Finds all the eliminations that a technique can make on the board. Only
+/// the ones that remove some possible value.
+fn eliminations(board: &SudokuBoard, domains: &Domains, technique: Technique) -> Vec<Elimination> {
+    let fits = |positions: &[usize], value: u8| -> Vec<usize> {
+        positions
+            .iter()
+            .copied()
+            .filter(|&pos| board[pos] == 0 && domains.contains(pos, value))
+            .collect()
+    };
+
+    let mut found = Vec::new();
+    match technique {
+        Technique::NakedSingle | Technique::HiddenSingle => {}
+        Technique::LockedCandidates => {
+            for unit in 0..UNITS {
+                for value in 1..=N2 as u8 {
+                    let cells = fits(&unit_positions(unit), value);
+                    if cells.len() < 2 {
+                        continue;
+                    }
+                    // two positions share at most a row or column and a group
+                    for other in (0..UNITS).filter(|&other| other != unit) {
+                        let positions = unit_positions(other);
+                        if !cells.iter().all(|pos| positions.contains(pos)) {
+                            continue;
+                        }
+                        let removed: Vec<(usize, u8)> = fits(&positions, value)
+                            .into_iter()
+                            .filter(|pos| !cells.contains(pos))
+                            .map(|pos| (pos, value))
+                            .collect();
+                        if !removed.is_empty() {
+                            found.push(Elimination {
+                                technique,
+                                cells: cells.clone(),
+                                values: vec![value],
+                                unit: UnitKind::of_unit(other),
+                                removed,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Technique::NakedPair => {
+            for unit in 0..UNITS {
+                let positions = unit_positions(unit);
+                let pairs: Vec<usize> = positions
+                    .iter()
+                    .copied()
+                    .filter(|&pos| board[pos] == 0 && domains.values(pos).count() == 2)
+                    .collect();
+                for (i, &a) in pairs.iter().enumerate() {
+                    for &b in pairs[i + 1..].iter() {
+                        if domains.domains[a] != domains.domains[b] {
+                            continue;
+                        }
+                        let values: Vec<u8> = domains.values(a).collect();
+                        let removed: Vec<(usize, u8)> = positions
+                            .iter()
+                            .copied()
+                            .filter(|&pos| pos != a && pos != b && board[pos] == 0)
+                            .flat_map(|pos| {
+                                values
+                                    .iter()
+                                    .filter(move |&&value| domains.contains(pos, value))
+                                    .map(move |&value| (pos, value))
+                            })
+                            .collect();
+                        if !removed.is_empty() {
+                            found.push(Elimination {
+                                technique,
+                                cells: vec![a, b],
+                                values,
+                                unit: UnitKind::of_unit(unit),
+                                removed,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Technique::HiddenPair => {
+            for unit in 0..UNITS {
+                let positions = unit_positions(unit);
+                let value_fits: Vec<Vec<usize>> = (1..=N2 as u8)
+                    .map(|value| fits(&positions, value))
+                    .collect();
+                for first in 1..=N2 as u8 {
+                    let cells = &value_fits[first as usize - 1];
+                    if cells.len() != 2 {
+                        continue;
+                    }
+                    for second in first + 1..=N2 as u8 {
+                        if value_fits[second as usize - 1] != *cells {
+                            continue;
+                        }
+                        let removed: Vec<(usize, u8)> = cells
+                            .iter()
+                            .flat_map(|&pos| {
+                                domains
+                                    .values(pos)
+                                    .filter(move |&value| value != first && value != second)
+                                    .map(move |value| (pos, value))
+                            })
+                            .collect();
+                        if !removed.is_empty() {
+                            found.push(Elimination {
+                                technique,
+                                cells: cells.clone(),
+                                values: vec![first, second],
+                                unit: UnitKind::of_unit(unit),
+                                removed,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Next move of the logical solver
+enum Move {
+    Place(SolveStep),
+    Eliminate(Elimination),
+}
+
+/// Finds the move of the easiest of the `techniques` that can be applied.
+fn next_move(board: &SudokuBoard, domains: &Domains, techniques: &[Technique]) -> Option<Move> {
+    for &technique in techniques {
+        if let Some(&(pos, value)) = placements(board, domains, technique).first() {
+            return Some(Move::Place(SolveStep {
+                pos,
+                value,
+                technique,
+            }));
+        }
+        if let Some(elimination) = eliminations(board, domains, technique).into_iter().next() {
+            return Some(Move::Eliminate(elimination));
+        }
+    }
+    None
+}
+
+/// Positions with a value that justify placing `value` in `pos` with a
+/// single: the ones that rule out the rest of values of the position for a
+/// naked single, and the ones that rule out `value` from the rest of the
+/// unit for a hidden single.
+fn single_cells(board: &SudokuBoard, domains: &Domains, step: &SolveStep) -> Vec<usize> {
+    let holding = |pos: usize, value: u8| adjacent_positions(pos).find(|&p| board[p] == value);
+
+    let mut cells: Vec<usize> = match step.technique {
+        Technique::NakedSingle => (1..=N2 as u8)
+            .filter(|&value| value != step.value)
+            .filter_map(|value| holding(step.pos, value))
+            .collect(),
+        _ => {
+            let hidden_in = (0..UNITS)
+                .map(unit_positions)
+                .filter(|positions| positions.contains(&step.pos))
+                .find(|positions| {
+                    positions.iter().all(|&p| {
+                        p == step.pos || board[p] != 0 || !domains.contains(p, step.value)
+                    })
+                });
+            hidden_in
+                .iter()
+                .flatten()
+                .filter(|&&p| p != step.pos && board[p] == 0)
+                .filter_map(|&p| holding(p, step.value))
+                .collect()
+        }
+    };
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+impl SudokuBoard {
+    /// Returns all the placements that the technique can make on the board
+    /// right now, without applying any of them. For example, to show how
+    /// many naked singles are available. Techniques that only remove
+    /// possible values, like [Technique::NakedPair], never place one, see
+    /// [SudokuBoard::eliminations_by].
+    pub fn resolvable_by(&self, technique: Technique) -> Vec<(usize, u8)> {
+        placements(self, &Domains::calculate_domains(self), technique)
+    }
+
+    /// Returns every empty position that has only one possible value right
+    /// now, with that value, in increasing order of position. The same as
+    /// [SudokuBoard::resolvable_by] with [Technique::NakedSingle]: applying
+    /// them and asking again shows the puzzle unfold step by step.
+    pub fn naked_singles(&self) -> Vec<(usize, u8)> {
+        self.resolvable_by(Technique::NakedSingle)
+    }
+
+    /// Returns every value that only fits in one empty position of a row,
+    /// column or group right now, even if the position has other possible
+    /// values, with that position and the unit, as its kind and its index
+    /// from 0 to N2 - 1. A value hidden in more than one unit of the same
+    /// position is returned once for each. Ordered by unit, first the rows,
+    /// then the columns and then the groups, and then by value.
+    pub fn hidden_singles(&self) -> Vec<(usize, u8, (UnitKind, usize))> {
+        hidden_singles(self, &Domains::calculate_domains(self))
+            .into_iter()
+            .map(|(pos, value, unit)| (pos, value, UnitKind::of_unit(unit)))
+            .collect()
+    }
+
+    /// Returns all the eliminations that the technique can make on the board
+    /// right now, without applying any of them. Techniques that place values,
+    /// like [Technique::NakedSingle], never remove one, see
+    /// [SudokuBoard::resolvable_by].
+    pub fn eliminations_by(&self, technique: Technique) -> Vec<Elimination> {
+        eliminations(self, &Domains::calculate_domains(self), technique)
+    }
+
+    /// Solves a copy of the board applying the easiest technique available
+    /// at each step, until it is complete or no technique can be applied.
+    pub(crate) fn logical_solve(&self) -> LogicalSolve {
+        self.logical_solve_with(Technique::all())
+    }
+
+    /// Like [SudokuBoard::logical_solve] but only with the `techniques`,
+    /// which must be sorted from the easiest.
+    pub(crate) fn logical_solve_with(&self, techniques: &[Technique]) -> LogicalSolve {
+        let mut board = self.clone();
+        let mut domains = Domains::calculate_domains(&board);
+        let mut steps = Vec::new();
+        let mut applied = Vec::new();
+
+        while let Some(next) = next_move(&board, &domains, techniques) {
+            match next {
+                Move::Place(step) => {
+                    board[step.pos] = step.value;
+                    domains.update_domains(step.pos, step.value);
+                    steps.push(step);
+                }
+                Move::Eliminate(elimination) => {
+                    for &(pos, value) in &elimination.removed {
+                        domains.remove(pos, value);
+                    }
+                    applied.push(elimination);
+                }
+            }
+        }
+
+        let remaining = board.iter().filter(|&&value| value == 0).count();
+        LogicalSolve {
+            steps,
+            eliminations: applied,
+            solved: remaining == 0,
+            remaining,
+        }
+    }
+
+    /// Returns the easiest value that can be deduced from the board as it
+    /// is now, without changing it, to nudge a player one step at a time. If
+    /// the value needs eliminations first, they are included in the hint.
+    /// `None` if the board is complete or some value has to be guessed.
+    pub fn hint(&self) -> Option<Hint> {
+        let mut domains = Domains::calculate_domains(self);
+        let mut applied: Vec<Elimination> = Vec::new();
+
+        loop {
+            match next_move(self, &domains, Technique::all())? {
+                Move::Place(step) => {
+                    let mut cells = single_cells(self, &domains, &step);
+                    cells.extend(applied.iter().flat_map(|e| e.cells.iter().copied()));
+                    cells.sort_unstable();
+                    cells.dedup();
+                    let technique = applied
+                        .iter()
+                        .map(|e| e.technique)
+                        .fold(step.technique, Ord::max);
+                    return Some(Hint {
+                        pos: step.pos,
+                        value: step.value,
+                        technique,
+                        cells,
+                        eliminations: applied,
+                    });
+                }
+                Move::Eliminate(elimination) => {
+                    for &(pos, value) in &elimination.removed {
+                        domains.remove(pos, value);
+                    }
+                    applied.push(elimination);
+                }
+            }
+        }
+    }
+
+    /// Solves the board in place applying the techniques, like a human
+    /// would, and returns each value placed. If the techniques are not enough
+    /// and some value has to be guessed, returns `None` and the board is
+    /// not changed.
+    pub fn solve_logical(&mut self) -> Option<Vec<SolveStep>> {
+        let solve = self.logical_solve();
+        if !solve.solved {
+            return None;
+        }
+        for step in &solve.steps {
+            self[step.pos] = step.value;
+        }
+        Some(solve.steps)
+    }
+
+    /// Returns the placements in the order a human would likely make them,
+    /// using the easiest technique available at each step. `None` if the
+    /// techniques are not enough to solve the board and guessing is needed.
+    pub fn suggested_order(&self) -> Option<Vec<(usize, u8, Technique)>> {
+        let solve = self.logical_solve();
+        if solve.solved {
+            Some(
+                solve
+                    .steps
+                    .into_iter()
+                    .map(|step| (step.pos, step.value, step.technique))
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Fills in place all the positions that the techniques can deduce, and
+    /// when they are not enough, returns the position where a value has to be
+    /// guessed: the one with the fewest possible values.
+    pub fn solve_until_guess(&mut self) -> SolveUntilGuess {
+        for step in self.logical_solve().steps {
+            self[step.pos] = step.value;
+        }
+
+        let domains = Domains::calculate_domains(self);
+        let guess = (0..SIZE)
+            .filter(|&pos| self[pos] == 0)
+            .map(|pos| {
+                let candidates: Vec<u8> = domains.values(pos).collect();
+                (pos, candidates)
+            })
+            .min_by_key(|(_, candidates)| candidates.len());
+
+        match guess {
+            None => SolveUntilGuess::Solved,
+            Some((pos, candidates)) if candidates.is_empty() => {
+                SolveUntilGuess::Contradiction { pos }
+            }
+            Some((pos, candidates)) => SolveUntilGuess::Guess { pos, candidates },
+        }
+    }
+
+    /// Grades the difficulty of the board by the hardest technique needed
+    /// to solve it:
+    /// - [Difficulty::Easy]: only naked singles.
+    /// - [Difficulty::Normal]: hidden singles.
+    /// - [Difficulty::Hard]: locked candidates, naked pairs or hidden pairs.
+    /// - [Difficulty::Insane]: the techniques are not enough and some value
+    ///   has to be guessed.
+    ///
+    /// It works with any board, not only generated ones, so a collection of
+    /// puzzles can be sorted by their difficulty.
+    pub fn grade(&self) -> Difficulty {
+        let hardest = *Technique::all().last().unwrap();
+        self.grade_with(hardest).difficulty
+    }
+
+    /// Rates the difficulty of the board with a number instead of the
+    /// buckets of [SudokuBoard::grade]: the sum of the [Technique::weight]
+    /// of every value placed and every elimination made by the techniques,
+    /// plus [GUESS_WEIGHT] for each position they could not fill. A solved
+    /// board scores `0`.
+    pub fn difficulty_score(&self) -> f64 {
+        self.logical_solve().score()
+    }
+
+    /// Like [SudokuBoard::grade], but only with the techniques up to
+    /// `max_technique`. Anything harder counts as guessing.
+    pub fn grade_with(&self, max_technique: Technique) -> Grade {
+        let techniques: Vec<Technique> = Technique::all()
+            .iter()
+            .copied()
+            .filter(|&technique| technique <= max_technique)
+            .collect();
+        let solve = self.logical_solve_with(&techniques);
+        let hardest_technique = solve.techniques().last().copied();
+
+        let difficulty = if !solve.solved {
+            Difficulty::Insane
+        } else {
+            match hardest_technique {
+                Some(Technique::LockedCandidates)
+                | Some(Technique::NakedPair)
+                | Some(Technique::HiddenPair) => Difficulty::Hard,
+                Some(Technique::HiddenSingle) => Difficulty::Normal,
+                Some(Technique::NakedSingle) | None => Difficulty::Easy,
+            }
+        };
+
+        Grade {
+            difficulty,
+            hardest_technique,
+            requires_guessing: !solve.solved,
+        }
+    }
+}
+
+/// Grades all the boards with [SudokuBoard::grade], keeping their order.
+/// `on_progress` is called with the number of graded boards and the total
+/// after each step.
+///
+/// With the `rayon` feature, the boards are graded in parallel and
+/// `on_progress` is called after every chunk of boards instead.
+pub fn grade_batch(
+    boards: &[SudokuBoard],
+    mut on_progress: impl FnMut(usize, usize),
+) -> Vec<Difficulty> {
+    let total = boards.len();
+    let mut grades = Vec::with_capacity(total);
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        let chunk_size = rayon::current_num_threads().max(1);
+        for chunk in boards.chunks(chunk_size) {
+            grades.par_extend(chunk.par_iter().map(SudokuBoard::grade));
+            on_progress(grades.len(), total);
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    for board in boards {
+        grades.push(board.grade());
+        on_progress(grades.len(), total);
+    }
+
+    grades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn grade() {
+        let solved = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        assert_eq!(solved.grade(), Difficulty::Easy);
+
+        let mut one_missing = solved.clone();
+        one_missing[0] = 0;
+        assert_eq!(one_missing.grade(), Difficulty::Easy);
+
+        assert_eq!(SudokuBoard::default().grade(), Difficulty::Insane);
+
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut solution = s.clone();
+        solution.solve();
+        for step in s.logical_solve().steps {
+            assert_eq!(solution[step.pos], step.value);
+        }
+
+        let mut collection = [s, solved, one_missing];
+        collection.sort_by_key(|board| board.grade());
+        assert_eq!(collection[2].grade(), Difficulty::Insane);
+        assert!(collection[0].grade() < collection[2].grade());
+    }
+
+    #[test]
+    fn difficulty_score() {
+        let solved = SudokuBoard::try_from(
+            "542971638917386254836542791723859146469123875158467329384715962695238417271694583",
+        )
+        .unwrap();
+        assert_eq!(solved.difficulty_score(), 0.0);
+
+        let mut one_missing = solved.clone();
+        one_missing[0] = 0;
+        assert_eq!(
+            one_missing.difficulty_score(),
+            Technique::NakedSingle.weight()
+        );
+
+        assert_eq!(
+            SudokuBoard::default().difficulty_score(),
+            SIZE as f64 * GUESS_WEIGHT
+        );
+
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        assert!(s.difficulty_score() > s.empty_count() as f64);
+
+        let weights: Vec<f64> = Technique::all().iter().map(Technique::weight).collect();
+        assert!(weights.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(weights.iter().all(|&weight| weight < GUESS_WEIGHT));
+    }
+
+    #[test]
+    fn grade_with() {
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let grade = s.grade_with(Technique::HiddenPair);
+        assert_eq!(grade.difficulty, s.grade());
+        assert_eq!(
+            grade.requires_guessing,
+            grade.difficulty == Difficulty::Insane
+        );
+        let singles = s.grade_with(Technique::HiddenSingle);
+        assert!(singles.hardest_technique <= Some(Technique::HiddenSingle));
+        assert!(singles.requires_guessing || !grade.requires_guessing);
+
+        let naked = s.grade_with(Technique::NakedSingle);
+        assert_ne!(naked.hardest_technique, Some(Technique::HiddenSingle));
+        assert!(naked.requires_guessing || !grade.requires_guessing);
+
+        for &id in Technique::get_all_ids() {
+            let technique = Technique::try_from(id).unwrap();
+            assert_eq!(technique.as_str().replace(' ', ""), id);
+        }
+        assert!(Technique::try_from("xywing").is_err());
+    }
+
+    #[test]
+    fn eliminations() {
+        // the 1 and the 2 can only go in the first two positions
+        let board = SudokuBoard::try_from(
+            "..3456789........................................................................",
+        )
+        .unwrap();
+
+        let naked = board.eliminations_by(Technique::NakedPair);
+        assert_eq!(naked.len(), 1);
+        assert_eq!(naked[0].cells, vec![0, 1]);
+        assert_eq!(naked[0].values, vec![1, 2]);
+        assert_eq!(naked[0].unit, (UnitKind::Box, 0));
+        assert_eq!(naked[0].removed.len(), 12);
+
+        let locked = board.eliminations_by(Technique::LockedCandidates);
+        assert!(locked
+            .iter()
+            .all(|e| e.cells == vec![0, 1] && e.unit == (UnitKind::Box, 0)));
+        assert_eq!(locked.len(), 2);
+
+        assert!(board.eliminations_by(Technique::HiddenPair).is_empty());
+        assert!(board.eliminations_by(Technique::NakedSingle).is_empty());
+        assert!(board.resolvable_by(Technique::NakedPair).is_empty());
+
+        // no elimination removes the value of the solution
+        let mut found = 0;
+        for seed in &["ELIMINATION", "PAIRS", "LOCKED"] {
+            let puzzle = crate::puzzle::SudokuPuzzle::prepare()
+                .with_given_difficulty(Difficulty::Insane)
+                .show_solution(true)
+                .with_seed(seed)
+                .generate();
+            let solution = puzzle.solution.unwrap();
+            for &technique in &Technique::all()[2..] {
+                for elimination in puzzle.puzzle.eliminations_by(technique) {
+                    found += 1;
+                    assert!(elimination
+                        .removed
+                        .iter()
+                        .all(|&(pos, value)| solution[pos] != value));
+                }
+            }
+            for step in puzzle.puzzle.logical_solve().steps {
+                assert_eq!(solution[step.pos], step.value);
+            }
+        }
+        assert!(found > 0);
+    }
+
+    #[test]
+    fn hint() {
+        let solved = SudokuBoard::generate_from_seed(&"HINT");
+        let mut board = solved.clone();
+        board[40] = 0;
+        let hint = board.hint().unwrap();
+        assert_eq!((hint.pos, hint.value), (40, solved[40]));
+        assert_eq!(hint.technique, Technique::NakedSingle);
+        assert_eq!(hint.cells.len(), N2 - 1);
+        assert!(hint.cells.iter().all(|&pos| board[pos] != 0));
+        assert!(hint.eliminations.is_empty());
+        assert_eq!(board[40], 0);
+
+        assert_eq!(solved.hint(), None);
+        assert_eq!(SudokuBoard::default().hint(), None);
+
+        // every hint is a value of the solution
+        let s = SudokuBoard::try_from(
+            "..2....3.....86.5..365...91........6.691...7...8............9.......8.17.716..5.3",
+        )
+        .unwrap();
+        let mut solution = s.clone();
+        solution.solve();
+        let mut board = s;
+        while let Some(hint) = board.hint() {
+            assert_eq!(solution[hint.pos], hint.value);
+            assert!(!hint.cells.is_empty());
+            board[hint.pos] = hint.value;
+        }
+    }
+
+    #[test]
+    fn solve_logical() {
+        let solved = SudokuBoard::generate_from_seed(&"LOGICAL");
+        let mut board = solved.clone();
+        for pos in 0..N2 {
+            board[pos] = 0;
+        }
+
+        let steps = board.solve_logical().unwrap();
+        assert_eq!(steps.len(), N2);
+        assert_eq!(board, solved);
+        assert!(
+            steps
+                .iter()
+                .all(|step| solved[step.pos] == step.value
+                    && step.technique == Technique::NakedSingle)
+        );
+
+        let mut board = SudokuBoard::default();
+        board[0] = 1;
+        assert_eq!(board.solve_logical(), None);
+        assert_eq!(board.iter().filter(|&&value| value != 0).count(), 1);
+    }
+
+    #[test]
+    fn suggested_order() {
+        let solved = SudokuBoard::generate_from_seed(&"ORDER");
+        let mut board = solved.clone();
+        for pos in [0, 1, 2, 40, 80] {
+            board[pos] = 0;
+        }
+
+        let order = board.suggested_order().unwrap();
+        assert_eq!(order.len(), 5);
+        for (pos, value, technique) in order {
+            assert_eq!(solved[pos], value);
+            assert_eq!(technique, Technique::NakedSingle);
+            board[pos] = value;
+        }
+        assert_eq!(board, solved);
+
+        assert_eq!(SudokuBoard::default().suggested_order(), None);
+    }
+
+    #[test]
+    fn resolvable_by() {
+        let solved = SudokuBoard::generate_from_seed(&"RESOLVABLE");
+        let mut board = solved.clone();
+        board[0] = 0;
+        board[1] = 0;
+        assert_eq!(
+            board.resolvable_by(Technique::NakedSingle),
+            vec![(0, solved[0]), (1, solved[1])]
+        );
+        assert_eq!(board.resolvable_by(Technique::HiddenSingle).len(), 2);
+
+        let mut board = SudokuBoard::default();
+        board[0] = 1;
+        assert!(board.resolvable_by(Technique::NakedSingle).is_empty());
+        assert!(board.resolvable_by(Technique::HiddenSingle).is_empty());
+    }
+
+    #[test]
+    fn naked_singles() {
+        let solved = SudokuBoard::generate_from_seed(&"NAKED");
+        let mut board = solved.clone();
+        for pos in [5, 40, 80].iter() {
+            board[*pos] = 0;
+        }
+        assert_eq!(
+            board.naked_singles(),
+            vec![(5, solved[5]), (40, solved[40]), (80, solved[80])]
+        );
+        assert!(solved.naked_singles().is_empty());
+        assert!(SudokuBoard::default().naked_singles().is_empty());
+    }
+
+    #[test]
+    fn hidden_singles() {
+        let mut board = SudokuBoard::default();
+        // the 1 can only go at the start of the first row and of the first
+        // group, which has other possible values
+        board[1] = 2;
+        board[2] = 3;
+        board[N2 + 3] = 1;
+        board[2 * N2 + 6] = 1;
+        let singles = board.hidden_singles();
+        assert!(singles.contains(&(0, 1, (UnitKind::Row, 0))));
+        assert!(singles.contains(&(0, 1, (UnitKind::Box, 0))));
+        assert!(!singles.contains(&(0, 1, (UnitKind::Column, 0))));
+        assert!(board.naked_singles().is_empty());
+        assert!(singles.iter().all(|&(pos, value, _)| board
+            .resolvable_by(Technique::HiddenSingle)
+            .contains(&(pos, value))));
+
+        let solved = SudokuBoard::generate_from_seed(&"HIDDEN");
+        let mut board = solved.clone();
+        board[40] = 0;
+        assert_eq!(
+            board.hidden_singles(),
+            vec![
+                (40, solved[40], (UnitKind::Row, 4)),
+                (40, solved[40], (UnitKind::Column, 4)),
+                (40, solved[40], (UnitKind::Box, 4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_until_guess() {
+        let solved = SudokuBoard::generate_from_seed(&"GUESS");
+        let mut board = solved.clone();
+        board[10] = 0;
+        board[50] = 0;
+        assert_eq!(board.solve_until_guess(), SolveUntilGuess::Solved);
+        assert_eq!(board, solved);
+
+        let mut board = SudokuBoard::default();
+        board[0] = 1;
+        assert_eq!(
+            board.solve_until_guess(),
+            SolveUntilGuess::Guess {
+                pos: 1,
+                candidates: vec![2, 3, 4, 5, 6, 7, 8, 9]
+            }
+        );
+
+        let mut board = SudokuBoard::try_from(
+            "12345678.........9...............................................................",
+        )
+        .unwrap();
+        assert_eq!(
+            board.solve_until_guess(),
+            SolveUntilGuess::Contradiction { pos: 8 }
+        );
+    }
+
+    #[test]
+    fn grade_batch() {
+        let boards = vec![
+            SudokuBoard::generate_from_seed(&"GRADE"),
+            SudokuBoard::default(),
+            SudokuBoard::generate_from_seed(&"BATCH"),
+        ];
+        let mut progress = Vec::new();
+        let grades = super::grade_batch(&boards, |done, total| progress.push((done, total)));
+
+        assert_eq!(
+            grades,
+            boards.iter().map(SudokuBoard::grade).collect::<Vec<_>>()
+        );
+        assert_eq!(grades[1], Difficulty::Insane);
+        assert_eq!(progress.last(), Some(&(3, 3)));
+        assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{N, N2};
+use super::{N, N2, SIZE};
 
 #[inline]
 pub const fn to_pos(row: usize, col: usize) -> usize {
@@ -10,15 +10,22 @@ pub const fn to_row_col(pos: usize) -> (usize, usize) {
     (pos / N2, pos % N2)
 }
 
-pub fn row_positions(row: usize) -> impl Iterator<Item = usize> + ExactSizeIterator {
+/// Index of the group of a position, from 0 to N2 - 1 in reading order.
+#[inline]
+pub const fn group_index(pos: usize) -> usize {
+    let (row, col) = to_row_col(pos);
+    (row / N) * N + col / N
+}
+
+pub fn row_positions(row: usize) -> impl ExactSizeIterator<Item = usize> {
     (0..N2).map(move |i| to_pos(row, i))
 }
 
-pub fn col_positions(col: usize) -> impl Iterator<Item = usize> + ExactSizeIterator {
+pub fn col_positions(col: usize) -> impl ExactSizeIterator<Item = usize> {
     (0..N2).map(move |i| to_pos(i, col))
 }
 
-pub fn group_positions(row: usize, col: usize) -> impl Iterator<Item = usize> + ExactSizeIterator {
+pub fn group_positions(row: usize, col: usize) -> impl ExactSizeIterator<Item = usize> {
     let group_row = row - row % N;
     let group_col = col - col % N;
 
@@ -28,11 +35,140 @@ pub fn group_positions(row: usize, col: usize) -> impl Iterator<Item = usize> +
     })
 }
 
-pub fn adjacent_positions(pos: usize) -> impl Iterator<Item = usize> + ExactSizeIterator {
+/// Positions of the group with the index given by [group_index].
+pub fn group_positions_by_index(index: usize) -> impl ExactSizeIterator<Item = usize> {
+    group_positions((index / N) * N, (index % N) * N)
+}
+
+pub fn adjacent_positions(pos: usize) -> impl ExactSizeIterator<Item = usize> {
     let (row, col) = to_row_col(pos);
     AdjacentPositionsIterator::new(row, col)
 }
 
+/// Returns true if `a` and `b` are different positions sharing a row, column
+/// or group.
+pub fn is_peer(a: usize, b: usize) -> bool {
+    let (a_row, a_col) = to_row_col(a);
+    let (b_row, b_col) = to_row_col(b);
+    a != b
+        && (a_row == b_row || a_col == b_col || (a_row / N == b_row / N && a_col / N == b_col / N))
+}
+
+/// Positions of the two main diagonals that share one with `pos`, without
+/// `pos` itself. None if `pos` is not in a diagonal.
+pub fn diagonal_positions(pos: usize) -> impl Iterator<Item = usize> {
+    let (row, col) = to_row_col(pos);
+    (0..N2)
+        .filter(move |_| row == col)
+        .map(|i| to_pos(i, i))
+        .chain(
+            (0..N2)
+                .filter(move |_| row + col == N2 - 1)
+                .map(|i| to_pos(i, N2 - 1 - i)),
+        )
+        .filter(move |&p| p != pos)
+}
+
+/// Positions a knight's move away from `pos`, inside the board.
+pub fn knight_positions(pos: usize) -> impl Iterator<Item = usize> {
+    const MOVES: [(isize, isize); 8] = [
+        (-2, -1),
+        (-2, 1),
+        (-1, -2),
+        (-1, 2),
+        (1, -2),
+        (1, 2),
+        (2, -1),
+        (2, 1),
+    ];
+    moved_positions(pos, &MOVES)
+}
+
+/// Positions a king's move away from `pos`, the 8 surrounding ones inside
+/// the board.
+pub fn king_positions(pos: usize) -> impl Iterator<Item = usize> {
+    const MOVES: [(isize, isize); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+    moved_positions(pos, &MOVES)
+}
+
+/// Positions orthogonally adjacent to `pos`, the ones up, down, left and
+/// right of it inside the board.
+pub fn orthogonal_positions(pos: usize) -> impl Iterator<Item = usize> {
+    const MOVES: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+    moved_positions(pos, &MOVES)
+}
+
+/// Positions at each `(row, col)` offset from `pos` that fall inside the
+/// board.
+fn moved_positions(pos: usize, moves: &'static [(isize, isize)]) -> impl Iterator<Item = usize> {
+    let (row, col) = to_row_col(pos);
+    moves.iter().filter_map(move |&(d_row, d_col)| {
+        let (row, col) = (row as isize + d_row, col as isize + d_col);
+        if (0..N2 as isize).contains(&row) && (0..N2 as isize).contains(&col) {
+            Some(to_pos(row as usize, col as usize))
+        } else {
+            None
+        }
+    })
+}
+
+/// Set of positions of the board, as a bitset.
+#[derive(Clone, Default)]
+pub struct PositionSet([u64; (SIZE + 63) / 64]);
+
+impl PositionSet {
+    pub fn insert(&mut self, pos: usize) {
+        self.0[pos / 64] |= 1 << (pos % 64);
+    }
+
+    pub fn remove(&mut self, pos: usize) {
+        self.0[pos / 64] &= !(1 << (pos % 64));
+    }
+
+    /// Positions in the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                // clear the lowest bit set
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+/// Number of units of the board: the rows, the columns and the groups.
+pub const UNITS: usize = N2 * 3;
+
+/// Positions of a unit, from 0 to [UNITS] - 1. First are the rows, then the
+/// columns and then the groups, in the order of [group_index].
+pub fn unit_positions(unit: usize) -> [usize; N2] {
+    let index = unit % N2;
+    let mut positions = [0; N2];
+    for (i, p) in positions.iter_mut().enumerate() {
+        *p = match unit / N2 {
+            0 => to_pos(index, i),
+            1 => to_pos(i, index),
+            _ => to_pos((index / N) * N + i / N, (index % N) * N + i % N),
+        };
+    }
+    positions
+}
+
 struct AdjacentPositionsIterator {
     i: usize,
     row: usize,
@@ -132,6 +268,25 @@ impl ExactSizeIterator for AdjacentPositionsIterator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn test_knight_king() {
+        let mut corner: Vec<usize> = knight_positions(0).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![to_pos(1, 2), to_pos(2, 1)]);
+        assert_eq!(knight_positions(to_pos(4, 4)).count(), 8);
+
+        let mut corner: Vec<usize> = king_positions(80).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![to_pos(7, 7), to_pos(7, 8), to_pos(8, 7)]);
+        assert_eq!(king_positions(to_pos(0, 4)).count(), 5);
+        assert_eq!(king_positions(to_pos(4, 4)).count(), 8);
+
+        let mut corner: Vec<usize> = orthogonal_positions(8).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![7, to_pos(1, 8)]);
+        assert_eq!(orthogonal_positions(to_pos(4, 4)).count(), 4);
+    }
+
     #[test]
     fn test_row() {
         let mut it = row_positions(2);
@@ -161,6 +316,32 @@ mod tests {
         assert_eq!(it.next(), None)
     }
 
+    #[test]
+    fn test_group_index() {
+        for g in 0..N2 {
+            for pos in group_positions_by_index(g) {
+                assert_eq!(group_index(pos), g);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_positions() {
+        assert!(row_positions(4).eq(unit_positions(4).iter().copied()));
+        assert!(col_positions(4).eq(unit_positions(N2 + 4).iter().copied()));
+        assert!(group_positions(3, 6).eq(unit_positions(N2 * 2 + 5).iter().copied()));
+    }
+
+    #[test]
+    fn test_is_peer() {
+        for pos in 0..N2 * N2 {
+            let adjacent: Vec<_> = adjacent_positions(pos).collect();
+            for other in 0..N2 * N2 {
+                assert_eq!(is_peer(pos, other), adjacent.contains(&other));
+            }
+        }
+    }
+
     #[test]
     fn test_adjacent() {
         if N == 3 {
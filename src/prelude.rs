@@ -1,6 +1,17 @@
 //! Basic import to use the library with only one `use`.
 
-pub use crate::board::SudokuBoard;
-pub use crate::puzzle::{Difficulty, Generator, GeneratorDifficulty, SudokuPuzzle};
+#[cfg(feature = "svg")]
+pub use crate::board::SvgOptions;
+pub use crate::board::{
+    Cell, CellMut, ParseError, SolveOutcome, SolveStats, SolverConfig, SudokuBoard, Symmetry,
+    UnitKind, Unsolvable, Variant,
+};
+pub use crate::killer::Cage;
+pub use crate::logic::{
+    grade_batch, Elimination, Grade, Hint, SolveStep, SolveUntilGuess, Technique,
+};
+pub use crate::puzzle::{
+    verify_seed, CsvColumn, Difficulty, Generator, GeneratorDifficulty, SudokuPuzzle,
+};
 pub use crate::*;
-pub use std::convert::TryFrom;
+pub use core::convert::TryFrom;
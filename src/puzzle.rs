@@ -1,17 +1,23 @@
 //! Contains funcionality of a sudoku puzzle: an unsolved
 //! sudoku to present to the user.
 
-use crate::board::SudokuBoard;
-use crate::SIZE;
+use crate::alloc_prelude::*;
+use crate::board::{SudokuBoard, Symmetry};
+use crate::logic::Technique;
+use crate::pos_util::group_index;
+use crate::{N2, SIZE};
 
-use std::time::{Duration, Instant};
+use core::time::Duration;
 
+use core::cell::RefCell;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Range;
+#[cfg(feature = "std")]
 use rand::distributions::Alphanumeric;
 use rand::prelude::*;
 use rand_pcg::Pcg64;
 use rand_seeder::Seeder;
-use std::convert::TryFrom;
-use std::fmt;
 
 /// A sudoku puzzle, a pair of a puzzle and a solution to it. Also gives some
 /// stats about the puzzle.
@@ -33,6 +39,8 @@ use std::fmt;
 ///
 /// println!("{}", puzzle);
 /// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SudokuPuzzle {
     /// Puzzle board generated
     pub puzzle: SudokuBoard,
@@ -43,6 +51,8 @@ pub struct SudokuPuzzle {
 }
 
 /// Stats about a [SudokuPuzzle]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PuzzleStats {
     /// Number of empty positions
     pub empty_positions: usize,
@@ -56,6 +66,41 @@ pub struct PuzzleStats {
     pub times: (Duration, Duration),
     /// Seed of the puzzle
     pub seed: String,
+    /// If the generator was configured with [Symmetry::None], whether the
+    /// pattern of the puzzle ended up without any symmetry.
+    pub asymmetric: Option<bool>,
+    /// If the generator was configured with
+    /// [Generator::require_backtracking], whether the puzzle ended up needing
+    /// more than naked and hidden singles to be solved.
+    pub requires_backtracking: Option<bool>,
+    /// If the generator was configured with [Generator::no_trivial_start],
+    /// whether the puzzle ended up without any naked single to start with.
+    pub no_trivial_start: Option<bool>,
+    /// If the generator was configured with [Generator::grade_difficulty],
+    /// whether the grade of the puzzle ended up matching its difficulty.
+    pub graded: Option<bool>,
+    /// Techniques needed to solve the puzzle without guessing, from the
+    /// easiest to the hardest. See [SudokuBoard::grade].
+    pub techniques_used: Vec<Technique>,
+    /// Difficulty of the puzzle as a number, see
+    /// [SudokuBoard::difficulty_score].
+    pub score: f64,
+    /// If the generator was configured with [Generator::with_target_score],
+    /// whether the score of the puzzle ended up in the range.
+    pub in_target_score: Option<bool>,
+    /// If no given position can be removed without making the puzzle have
+    /// more than one solution, see [SudokuBoard::is_minimal].
+    pub is_minimal: bool,
+    /// If the generator was configured with [Generator::with_template], the
+    /// positions of the template that could not be removed without breaking
+    /// the uniqueness of the solution.
+    pub kept_from_template: Vec<usize>,
+    /// Every number drawn from the random number generator of the puzzle,
+    /// in order, to find where two generations with the same seed diverge.
+    /// The difficulty is chosen with another generator, not traced. Empty
+    /// for puzzles not made by a [Generator].
+    #[cfg(feature = "trace")]
+    pub rng_trace: Vec<u64>,
 }
 
 impl SudokuPuzzle {
@@ -65,35 +110,219 @@ impl SudokuPuzzle {
         Generator::default()
     }
 
+    /// Puzzle board generated
+    pub fn puzzle(&self) -> &SudokuBoard {
+        &self.puzzle
+    }
+
+    /// Solution of the board, only present if the [Generator] was configured
+    /// with [Generator::show_solution]
+    pub fn solution(&self) -> Option<&SudokuBoard> {
+        self.solution.as_ref()
+    }
+
+    /// Solution of the board. If it is not present, the puzzle is solved and
+    /// the solution is kept, so it is only solved once.
+    pub fn solution_or_solve(&mut self) -> &SudokuBoard {
+        let puzzle = &self.puzzle;
+        self.solution.get_or_insert_with(|| {
+            let mut solution = puzzle.clone();
+            solution.solve();
+            solution
+        })
+    }
+
+    /// Stats about the generated puzzle
+    pub fn stats(&self) -> &PuzzleStats {
+        &self.stats
+    }
+
+    /// Returns a compact code of the puzzle, its difficulty and its seed,
+    /// made only of uppercase letters and digits so it fits the alphanumeric
+    /// mode of QR codes. Use [SudokuPuzzle::from_share_code] to read it.
+    ///
+    /// The code starts with the version of the encoding, so codes from older
+    /// versions can still be read.
+    pub fn share_code(&self) -> String {
+        let difficulty = match self.stats.difficulty {
+            Difficulty::Easy => '0',
+            Difficulty::Normal => '1',
+            Difficulty::Hard => '2',
+            Difficulty::Insane => '3',
+        };
+        let mut code = String::from(SHARE_CODE_VERSION);
+        code.push(difficulty);
+        code.push_str(&base32_encode(&self.puzzle.to_packed()));
+        code.push_str(&base32_encode(self.stats.seed.as_bytes()));
+        code
+    }
+
+    /// Reads a puzzle from a code made with [SudokuPuzzle::share_code]. The
+    /// solution is not included, and the stats only have the seed, the
+    /// difficulty, the empty positions and if it is minimal.
+    pub fn from_share_code(code: &str) -> Result<Self, &'static str> {
+        if !code.is_ascii() {
+            return Err("Invalid share code");
+        }
+        if !code.starts_with(SHARE_CODE_VERSION) {
+            return Err("Unknown share code version");
+        }
+        let code = &code[SHARE_CODE_VERSION.len()..];
+        let difficulty = match code.chars().next() {
+            Some('0') => Difficulty::Easy,
+            Some('1') => Difficulty::Normal,
+            Some('2') => Difficulty::Hard,
+            Some('3') => Difficulty::Insane,
+            _ => return Err("Invalid share code difficulty"),
+        };
+        let board_len = base32_len((SIZE + 1) / 2);
+        if code.len() < 1 + board_len {
+            return Err("Share code too short");
+        }
+        let puzzle = base32_decode(&code[1..1 + board_len])
+            .and_then(|bytes| SudokuBoard::from_packed(&bytes).ok())
+            .ok_or("Invalid share code board")?;
+        let seed = base32_decode(&code[1 + board_len..])
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or("Invalid share code seed")?;
+
+        let stats = PuzzleStats {
+            empty_positions: puzzle.empty_count(),
+            difficulty,
+            possible_solutions: None,
+            times: (Duration::default(), Duration::default()),
+            seed,
+            asymmetric: None,
+            requires_backtracking: None,
+            no_trivial_start: None,
+            graded: None,
+            techniques_used: Vec::new(),
+            score: puzzle.difficulty_score(),
+            in_target_score: None,
+            is_minimal: puzzle.is_minimal(),
+            kept_from_template: Vec::new(),
+            #[cfg(feature = "trace")]
+            rng_trace: Vec::new(),
+        };
+        Ok(SudokuPuzzle {
+            puzzle,
+            solution: None,
+            stats,
+        })
+    }
+
     /// Prints the CSV head line when writting a puzzle as csv.
     pub fn csv_head() -> &'static str {
         "puzzle,solution,seed,empty_positions,difficulty,possible_solutions,board_time_us,puzzle_time_us"
     }
+
+    /// CSV head line with only the `columns`, in their order. See
+    /// [SudokuPuzzle::write_csv_fields].
+    pub fn csv_head_with(columns: &[CsvColumn]) -> String {
+        let names: Vec<&str> = columns.iter().map(CsvColumn::as_str).collect();
+        names.join(",")
+    }
+
+    /// Writes the puzzle as a CSV line, without the line break, with only
+    /// the `columns`, in their order. The alternate format of the puzzle is
+    /// the same with all the columns of [CsvColumn::all].
+    pub fn write_csv_fields(&self, f: &mut impl fmt::Write, columns: &[CsvColumn]) -> fmt::Result {
+        let s = &self.stats;
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match column {
+                CsvColumn::Puzzle => write!(f, "{:#}", self.puzzle)?,
+                CsvColumn::Solution => {
+                    if let Some(solution) = self.solution.as_ref() {
+                        write!(f, "{:#}", solution)?;
+                    }
+                }
+                CsvColumn::Seed => write!(f, "{}", s.seed)?,
+                CsvColumn::EmptyPositions => write!(f, "{}", s.empty_positions)?,
+                CsvColumn::Difficulty => write!(f, "{:#}", s.difficulty)?,
+                CsvColumn::PossibleSolutions => {
+                    if let Some(ps) = s.possible_solutions {
+                        write!(f, "{}", ps)?;
+                    }
+                }
+                CsvColumn::BoardTime => write!(f, "{}", s.times.0.as_micros())?,
+                CsvColumn::PuzzleTime => write!(f, "{}", s.times.1.as_micros())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Column of the CSV format of a [SudokuPuzzle], see
+/// [SudokuPuzzle::write_csv_fields].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// The puzzle as a line
+    Puzzle,
+    /// The solution as a line, empty if it is not present
+    Solution,
+    /// [PuzzleStats::seed]
+    Seed,
+    /// [PuzzleStats::empty_positions]
+    EmptyPositions,
+    /// [PuzzleStats::difficulty]
+    Difficulty,
+    /// [PuzzleStats::possible_solutions], empty if they were not counted
+    PossibleSolutions,
+    /// Time to generate the complete board, in microseconds
+    BoardTime,
+    /// Time to generate the puzzle from the complete board, in microseconds
+    PuzzleTime,
+}
+
+impl CsvColumn {
+    /// Returns all the columns, in the order of [SudokuPuzzle::csv_head]
+    pub const fn all() -> &'static [CsvColumn; 8] {
+        &[
+            CsvColumn::Puzzle,
+            CsvColumn::Solution,
+            CsvColumn::Seed,
+            CsvColumn::EmptyPositions,
+            CsvColumn::Difficulty,
+            CsvColumn::PossibleSolutions,
+            CsvColumn::BoardTime,
+            CsvColumn::PuzzleTime,
+        ]
+    }
+
+    /// Returns the name of the column in the CSV head. These are the values
+    /// accepted by `try_from`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            CsvColumn::Puzzle => "puzzle",
+            CsvColumn::Solution => "solution",
+            CsvColumn::Seed => "seed",
+            CsvColumn::EmptyPositions => "empty_positions",
+            CsvColumn::Difficulty => "difficulty",
+            CsvColumn::PossibleSolutions => "possible_solutions",
+            CsvColumn::BoardTime => "board_time_us",
+            CsvColumn::PuzzleTime => "puzzle_time_us",
+        }
+    }
+}
+
+impl TryFrom<&str> for CsvColumn {
+    type Error = &'static str;
+    fn try_from(val: &str) -> Result<Self, Self::Error> {
+        CsvColumn::all()
+            .iter()
+            .copied()
+            .find(|column| column.as_str() == val)
+            .ok_or("Unknown CSV column")
+    }
 }
 
 impl fmt::Display for SudokuPuzzle {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if f.alternate() {
-            write!(f, "{:#},", self.puzzle)?;
-            if let Some(solution) = self.solution.as_ref() {
-                write!(f, "{:#}", solution)?;
-            }
-            write!(f, ",")?;
-            let s = &self.stats;
-            write!(
-                f,
-                "{seed},{empty},{difficulty:#},{possible_sol},{boardtime},{puzzletime}",
-                seed = s.seed,
-                empty = s.empty_positions,
-                difficulty = s.difficulty,
-                possible_sol = if let Some(ps) = s.possible_solutions {
-                    ps.to_string()
-                } else {
-                    String::default()
-                },
-                boardtime = s.times.0.as_micros(),
-                puzzletime = s.times.1.as_micros(),
-            )
+            self.write_csv_fields(f, CsvColumn::all())
         } else {
             write!(f, "{}", self.puzzle)?;
             writeln!(f, "ID: {}", self.stats.seed)?;
@@ -109,7 +338,71 @@ impl fmt::Display for SudokuPuzzle {
     }
 }
 
+const SHARE_CODE_VERSION: &str = "1";
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Number of base32 characters needed for `bytes`
+const fn base32_len(bytes: usize) -> usize {
+    (bytes * 8 + 4) / 5
+}
+
+/// Base32 (RFC 4648) without padding
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(base32_len(bytes.len()));
+    let (mut buffer, mut bits) = (0u16, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    encoded
+}
+
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(s.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u16, 0);
+    for c in s.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|&a| a == c)? as u16;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    // the remaining bits are only padding
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+/// Checks that the puzzle generated with the `seed`, `difficulty` and
+/// uniqueness setting, with the rest of settings by default, is `expected`.
+///
+/// Useful to confirm that a puzzle really comes from the claimed seed.
+pub fn verify_seed(
+    seed: &str,
+    difficulty: Difficulty,
+    unique: bool,
+    expected: &SudokuBoard,
+) -> bool {
+    let puzzle = SudokuPuzzle::prepare()
+        .with_seed(seed)
+        .with_given_difficulty(difficulty)
+        .unique_solution(unique)
+        .generate();
+    puzzle.puzzle == *expected
+}
+
 /// Configurable [SudokuPuzzle] generator
+#[derive(Clone)]
 pub struct Generator {
     unique: bool,
     seed: Option<String>,
@@ -118,63 +411,281 @@ pub struct Generator {
     count_solutions: bool,
     max_count_solutions: usize,
     show_solution: bool,
+    min_empties_per_box: usize,
+    symmetry: Option<Symmetry>,
+    require_backtracking: bool,
+    no_trivial_start: bool,
+    measure_time: bool,
+    grade_difficulty: bool,
+    target_score: Option<(f64, f64)>,
+    clue_count: Option<usize>,
+    difficulty_blanks: [usize; 4],
+    minimal: bool,
+    template: Option<[[bool; N2]; N2]>,
+    cache: Option<RefCell<Option<(CacheKey, SudokuPuzzle)>>>,
+}
+
+/// Everything that changes the puzzle generated, to know if a cached puzzle
+/// can be reused.
+#[derive(Clone, PartialEq)]
+struct CacheKey {
+    seed: String,
+    difficulty: Difficulty,
+    unique: bool,
+    count_solutions: bool,
+    max_count_solutions: usize,
+    show_solution: bool,
+    min_empties_per_box: usize,
+    symmetry: Option<Symmetry>,
+    require_backtracking: bool,
+    no_trivial_start: bool,
+    measure_time: bool,
+    grade_difficulty: bool,
+    target_score: Option<(f64, f64)>,
+    clue_count: Option<usize>,
+    difficulty_blanks: [usize; 4],
+    minimal: bool,
+    template: Option<[[bool; N2]; N2]>,
+}
+
+/// Puzzle made by [Generator::remove_positions]
+struct Removal {
+    puzzle: SudokuBoard,
+    removed: usize,
+    asymmetric: Option<bool>,
+    is_minimal: bool,
 }
 
+/// Result of each requirement of the generator, `None` if not enabled
+struct Requirements {
+    requires_backtracking: Option<bool>,
+    no_trivial_start: Option<bool>,
+    graded: Option<bool>,
+    in_target_score: Option<bool>,
+}
+
+impl Requirements {
+    fn satisfied(&self) -> bool {
+        self.requires_backtracking != Some(false)
+            && self.no_trivial_start != Some(false)
+            && self.graded != Some(false)
+            && self.in_target_score != Some(false)
+    }
+}
+
+/// Random number generator of the puzzles, traced with the `trace` feature.
+#[cfg(not(feature = "trace"))]
+type GeneratorRng = Pcg64;
+#[cfg(feature = "trace")]
+type GeneratorRng = TraceRng<Pcg64>;
+
+/// Records every number drawn from `rng`, see [PuzzleStats::rng_trace].
+#[cfg(feature = "trace")]
+struct TraceRng<R> {
+    rng: R,
+    trace: Vec<u64>,
+}
+
+#[cfg(feature = "trace")]
+impl<R: RngCore> RngCore for TraceRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.trace.push(value.into());
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        self.trace.push(value);
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        for chunk in dest.chunks(8) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.trace.push(u64::from_le_bytes(bytes));
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Times the generator removes values from the same solution to meet its
+/// requirements.
+const MAX_GENERATION_ATTEMPTS: usize = 32;
+
 impl Generator {
     /// Generate the a puzzle from the generator.
+    ///
+    /// # Panics
+    /// Without the `std` feature there is no source of randomness, so if no
+    /// seed was given with [Generator::with_seed].
     pub fn generate(&self) -> SudokuPuzzle {
-        let seed = self.seed.as_ref().cloned().unwrap_or_else(|| {
-            thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(self.seed_length)
-                .map(char::from)
-                .collect()
-        });
+        let seed = self
+            .seed
+            .as_ref()
+            .cloned()
+            .unwrap_or_else(|| self.random_seed());
+        self.generate_seeded(seed)
+    }
 
+    #[cfg(feature = "std")]
+    fn random_seed(&self) -> String {
+        thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(self.seed_length)
+            .map(char::from)
+            .collect()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn random_seed(&self) -> String {
+        panic!("A seed is needed to generate puzzles without the std feature")
+    }
+
+    /// Generates `n` puzzles. With [Generator::with_seed], each puzzle gets
+    /// its own seed derived from it, so the batch is reproducible but the
+    /// puzzles are different: the first one uses the seed itself, like
+    /// [Generator::generate], and the rest the seed followed by `-` and their
+    /// index, like `seed-1`. Without a seed, each puzzle gets a random one.
+    pub fn generate_batch(&self, n: usize) -> Vec<SudokuPuzzle> {
+        match &self.seed {
+            Some(seed) => (0..n)
+                .map(|i| {
+                    if i == 0 {
+                        self.generate_seeded(seed.clone())
+                    } else {
+                        self.generate_seeded(format!("{}-{}", seed, i))
+                    }
+                })
+                .collect(),
+            None => (0..n).map(|_| self.generate()).collect(),
+        }
+    }
+
+    fn generate_seeded(&self, seed: String) -> SudokuPuzzle {
+        // everything random comes from the seed, so the same seed always
+        // generates the same puzzle
+        let mut difficulty_rng: Pcg64 = Seeder::from(&(seed.as_str(), "difficulty")).make_rng();
         let difficulty = match &self.difficulty {
             GeneratorDifficulty::Given(d) => d.clone(),
             GeneratorDifficulty::Random => {
                 let difficulty_name = Difficulty::get_all()
-                    .choose(&mut thread_rng())
+                    .choose(&mut difficulty_rng)
                     .expect("No difficulties while generating a random one");
                 Difficulty::try_from(*difficulty_name)
                     .expect("Difficulty could not be built while generating a random one")
             }
+            GeneratorDifficulty::Weighted(weights) => weights
+                .choose_weighted(&mut difficulty_rng, |(_, weight)| *weight)
+                .map(|(difficulty, _)| difficulty.clone())
+                .unwrap_or(Difficulty::Normal),
         };
 
-        let empty_positions = match &difficulty {
-            Difficulty::Easy => 25,
-            Difficulty::Normal => 35,
-            Difficulty::Hard => 50,
-            Difficulty::Insane => 64,
-        };
+        let cache = self.cache.as_ref().map(|cache| {
+            let key = CacheKey {
+                seed: seed.clone(),
+                difficulty: difficulty.clone(),
+                unique: self.unique,
+                count_solutions: self.count_solutions,
+                max_count_solutions: self.max_count_solutions,
+                show_solution: self.show_solution,
+                min_empties_per_box: self.min_empties_per_box,
+                symmetry: self.symmetry,
+                require_backtracking: self.require_backtracking,
+                no_trivial_start: self.no_trivial_start,
+                measure_time: self.measure_time,
+                grade_difficulty: self.grade_difficulty,
+                target_score: self.target_score,
+                clue_count: self.clue_count,
+                difficulty_blanks: self.difficulty_blanks,
+                minimal: self.minimal,
+                template: self.template,
+            };
+            (cache, key)
+        });
+        if let Some((cache, key)) = &cache {
+            if let Some((cached_key, puzzle)) = cache.borrow().as_ref() {
+                if cached_key == key {
+                    return puzzle.clone();
+                }
+            }
+        }
 
-        let mut rng: Pcg64 = Seeder::from(seed.clone()).make_rng();
+        let puzzle = self.generate_puzzle(seed, difficulty);
 
-        let now = Instant::now();
-        let solution = SudokuBoard::generate(&mut rng);
-        let solution_time = now.elapsed();
+        if let Some((cache, key)) = cache {
+            *cache.borrow_mut() = Some((key, puzzle.clone()));
+        }
+        puzzle
+    }
 
-        let now = Instant::now();
-        let mut puzzle = solution.clone();
+    fn generate_puzzle(&self, seed: String, difficulty: Difficulty) -> SudokuPuzzle {
+        let empty_positions = match (self.clue_count, &difficulty) {
+            _ if self.template.is_some() => (0..SIZE).filter(|&pos| self.in_template(pos)).count(),
+            (Some(clues), _) => SIZE.saturating_sub(clues),
+            (None, Difficulty::Easy) => self.difficulty_blanks[0],
+            (None, Difficulty::Normal) => self.difficulty_blanks[1],
+            (None, Difficulty::Hard) => self.difficulty_blanks[2],
+            (None, Difficulty::Insane) => self.difficulty_blanks[3],
+        };
 
-        let mut positions: Vec<usize> = (0..SIZE).collect();
-        positions.shuffle(&mut rng);
+        let rng: Pcg64 = Seeder::from(seed.clone()).make_rng();
+        #[cfg(feature = "trace")]
+        let rng = TraceRng {
+            rng,
+            trace: Vec::new(),
+        };
+        let mut rng: GeneratorRng = rng;
 
-        let mut removed = 0;
-        for pos in positions {
-            let val = puzzle[pos];
-            puzzle[pos] = 0;
-            if !self.unique || puzzle.count_solutions(2) == 1 {
-                removed += 1;
-                if removed >= empty_positions {
-                    break;
-                }
+        // without measuring the time, or without a clock in `no_std`, the
+        // durations are left as zero
+        #[cfg(feature = "std")]
+        let start = || {
+            if self.measure_time {
+                Some(std::time::Instant::now())
             } else {
-                puzzle[pos] = val;
+                None
             }
-        }
-        let puzzle_time = now.elapsed();
+        };
+        #[cfg(feature = "std")]
+        let elapsed =
+            |start: Option<std::time::Instant>| start.map(|now| now.elapsed()).unwrap_or_default();
+        #[cfg(not(feature = "std"))]
+        let start = || None::<()>;
+        #[cfg(not(feature = "std"))]
+        let elapsed = |_: Option<()>| Duration::default();
+
+        let now = start();
+        let solution = SudokuBoard::generate_with(&mut rng);
+        let solution_time = elapsed(now);
+
+        let now = start();
+        // the removal is repeated, with the same solution, until the puzzle
+        // meets the requirements or there are no more attempts
+        let mut attempts = 0;
+        let (removal, requirements) = loop {
+            attempts += 1;
+            let removal = self.remove_positions(&solution, empty_positions, &mut rng);
+            let requirements = self.check_requirements(&removal.puzzle, &difficulty);
+
+            if requirements.satisfied() || attempts >= MAX_GENERATION_ATTEMPTS {
+                break (removal, requirements);
+            }
+        };
+        let puzzle_time = elapsed(now);
+        let puzzle = removal.puzzle;
+
+        let solve = puzzle.logical_solve();
+        let techniques_used = solve.techniques();
+        let kept_from_template = (0..SIZE)
+            .filter(|&pos| self.in_template(pos) && puzzle[pos] != 0)
+            .collect();
 
         let possible_solutions = if self.count_solutions {
             Some(puzzle.count_solutions(self.max_count_solutions))
@@ -183,11 +694,22 @@ impl Generator {
         };
 
         let stats = PuzzleStats {
-            empty_positions: removed,
+            empty_positions: removal.removed,
             difficulty,
             possible_solutions,
             times: (solution_time, puzzle_time),
             seed,
+            asymmetric: removal.asymmetric,
+            requires_backtracking: requirements.requires_backtracking,
+            no_trivial_start: requirements.no_trivial_start,
+            graded: requirements.graded,
+            techniques_used,
+            score: solve.score(),
+            in_target_score: requirements.in_target_score,
+            is_minimal: removal.is_minimal,
+            kept_from_template,
+            #[cfg(feature = "trace")]
+            rng_trace: rng.trace,
         };
 
         SudokuPuzzle {
@@ -201,6 +723,231 @@ impl Generator {
         }
     }
 
+    /// Checks if `pos` has to be empty in the template, see
+    /// [Generator::with_template]. False without a template.
+    fn in_template(&self, pos: usize) -> bool {
+        self.template
+            .map_or(false, |template| template[pos / N2][pos % N2])
+    }
+
+    /// Checks the puzzle against the requirements enabled in the generator.
+    fn check_requirements(&self, puzzle: &SudokuBoard, difficulty: &Difficulty) -> Requirements {
+        let requires_backtracking = if self.require_backtracking {
+            let singles = [Technique::NakedSingle, Technique::HiddenSingle];
+            Some(!puzzle.logical_solve_with(&singles).solved)
+        } else {
+            None
+        };
+        let no_trivial_start = if self.no_trivial_start {
+            Some(puzzle.resolvable_by(Technique::NakedSingle).is_empty())
+        } else {
+            None
+        };
+        let graded = if self.grade_difficulty {
+            Some(puzzle.grade() == *difficulty)
+        } else {
+            None
+        };
+        let in_target_score = self.target_score.map(|(min, max)| {
+            let score = puzzle.difficulty_score();
+            min <= score && score <= max
+        });
+        Requirements {
+            requires_backtracking,
+            no_trivial_start,
+            graded,
+            in_target_score,
+        }
+    }
+
+    /// Removes up to `empty_positions` values of the `solution` in a random
+    /// order, following the settings of the generator.
+    fn remove_positions(
+        &self,
+        solution: &SudokuBoard,
+        empty_positions: usize,
+        rng: &mut GeneratorRng,
+    ) -> Removal {
+        let mut puzzle = solution.clone();
+
+        let mut positions: Vec<usize> = (0..SIZE).collect();
+        positions.shuffle(rng);
+
+        // a template is the whole pattern, only its positions are removed
+        let template = self.template.is_some();
+        if template {
+            positions.retain(|&pos| self.in_template(pos));
+        }
+        let symmetry_setting = if template { None } else { self.symmetry };
+
+        // with a symmetry, a position is removed together with its mirror so
+        // the pattern stays symmetric. A position that is its own mirror,
+        // like the center with a 180 degrees rotation, is removed alone.
+        let symmetry = symmetry_setting.unwrap_or(Symmetry::None);
+        let orbit = |pos: usize| {
+            let mirror = symmetry.mirror(pos);
+            if mirror == pos {
+                vec![pos]
+            } else {
+                vec![pos, mirror]
+            }
+        };
+
+        let unique = self.unique;
+        let try_remove = |puzzle: &mut SudokuBoard, orbit: &[usize]| {
+            for &pos in orbit {
+                puzzle[pos] = 0;
+            }
+            if !unique || puzzle.has_unique_solution() {
+                true
+            } else {
+                for &pos in orbit {
+                    puzzle[pos] = solution[pos];
+                }
+                false
+            }
+        };
+
+        // the minimums can not go over the empty positions of the difficulty
+        let min_per_box = if template {
+            0
+        } else {
+            self.min_empties_per_box.min(empty_positions / N2)
+        };
+
+        // positions that could not be removed alone, with fewer givens they
+        // can not be removed either
+        let mut kept = [false; SIZE];
+        let mut removed = 0;
+        let mut removed_positions = Vec::with_capacity(empty_positions);
+        // first leave the minimum empty positions in every group. A position
+        // that cannot be removed now, will not be removable later.
+        if min_per_box > 0 {
+            let mut group_empties = [0; N2];
+            positions.retain(|&pos| {
+                if puzzle[pos] == 0 {
+                    // already removed as the mirror of another position
+                    return false;
+                }
+                if group_empties[group_index(pos)] >= min_per_box {
+                    return true;
+                }
+                let orbit = orbit(pos);
+                if removed + orbit.len() <= empty_positions {
+                    if try_remove(&mut puzzle, &orbit) {
+                        for &p in &orbit {
+                            group_empties[group_index(p)] += 1;
+                        }
+                        removed += orbit.len();
+                        removed_positions.extend(orbit);
+                    } else {
+                        kept[pos] = orbit.len() == 1;
+                    }
+                }
+                false
+            });
+        }
+
+        for pos in positions {
+            if removed >= empty_positions {
+                break;
+            }
+            if puzzle[pos] == 0 {
+                continue;
+            }
+            let orbit = orbit(pos);
+            if removed + orbit.len() > empty_positions {
+                continue;
+            }
+            if try_remove(&mut puzzle, &orbit) {
+                removed += orbit.len();
+                removed_positions.extend(orbit);
+            } else {
+                kept[pos] = orbit.len() == 1;
+            }
+        }
+
+        // keep removing, over the empty positions, until no given can be
+        if self.minimal && unique && !template {
+            let mut givens: Vec<usize> = (0..SIZE).filter(|&pos| puzzle[pos] != 0).collect();
+            givens.shuffle(rng);
+            for pos in givens {
+                if puzzle[pos] == 0 {
+                    continue;
+                }
+                let orbit = orbit(pos);
+                if try_remove(&mut puzzle, &orbit) {
+                    removed += orbit.len();
+                    removed_positions.extend(orbit);
+                } else {
+                    kept[pos] = orbit.len() == 1;
+                }
+            }
+        }
+
+        let asymmetric = if let Some(Symmetry::None) = symmetry_setting {
+            // give back the last removed positions until there is no symmetry.
+            // Adding a given never breaks the uniqueness of the solution.
+            loop {
+                let symmetries = puzzle.detect_symmetry();
+                if symmetries.is_empty() {
+                    break Some(true);
+                }
+                let restore = removed_positions.iter().rposition(|&pos| {
+                    symmetries.iter().any(|symmetry| {
+                        let mirror = symmetry.mirror(pos);
+                        mirror != pos && puzzle[mirror] == 0
+                    })
+                });
+                match restore {
+                    Some(i) => {
+                        let pos = removed_positions.remove(i);
+                        puzzle[pos] = solution[pos];
+                        removed -= 1;
+                    }
+                    None => break Some(false),
+                }
+            }
+        } else {
+            None
+        };
+
+        let is_minimal = if unique {
+            let givens: Vec<usize> = (0..SIZE)
+                .filter(|&pos| puzzle[pos] != 0 && !kept[pos])
+                .collect();
+            givens.into_iter().all(|pos| {
+                let removable = try_remove(&mut puzzle, &[pos]);
+                puzzle[pos] = solution[pos];
+                !removable
+            })
+        } else {
+            puzzle.is_minimal()
+        };
+
+        Removal {
+            puzzle,
+            removed,
+            asymmetric,
+            is_minimal,
+        }
+    }
+
+    /// Configures a generator to generate again the puzzle of a code made
+    /// with [SudokuPuzzle::share_code], so a single code is enough to share
+    /// it: with its seed and its difficulty, and with a unique solution if
+    /// the puzzle has one. The rest of the settings are the defaults, so a
+    /// puzzle generated with others, like a symmetry, is not the same.
+    ///
+    /// Fails like [SudokuPuzzle::from_share_code].
+    pub fn from_share_code(code: &str) -> Result<Generator, &'static str> {
+        let shared = SudokuPuzzle::from_share_code(code)?;
+        Ok(Generator::default()
+            .with_seed(&shared.stats.seed)
+            .with_given_difficulty(shared.stats.difficulty)
+            .unique_solution(shared.puzzle.has_unique_solution()))
+    }
+
     /// Configure if the puzzle should have an unique solution. `true` by default.
     pub fn unique_solution(mut self, unique: bool) -> Self {
         self.unique = unique;
@@ -214,12 +961,23 @@ impl Generator {
     }
 
     /// Configure that each time [Generator::generate] is called, a random
-    /// [Difficulty] is used.
+    /// [Difficulty] is used. The choice depends on the seed, so the same seed
+    /// gets the same difficulty.
     pub fn with_random_difficulty(mut self) -> Self {
         self.difficulty = GeneratorDifficulty::Random;
         self
     }
 
+    /// Configure that each time [Generator::generate] is called, a random
+    /// [Difficulty] is chosen with the given weights. The choice depends on
+    /// the seed, so the same seed gets the same difficulty. If the weights are
+    /// not valid, i.e., some is negative or all are zero, [Difficulty::Normal]
+    /// is used.
+    pub fn with_weighted_difficulty(mut self, weights: [(Difficulty, f64); 4]) -> Self {
+        self.difficulty = GeneratorDifficulty::Weighted(weights);
+        self
+    }
+
     /// Configures the generator difficulty directly to a given one or random.
     /// [Difficulty::Normal] by default.
     pub fn with_difficulty(mut self, difficulty: GeneratorDifficulty) -> Self {
@@ -258,6 +1016,163 @@ impl Generator {
         self.show_solution = do_show;
         self
     }
+
+    /// Configure if the last generated puzzle is kept to return a copy of it
+    /// when the same puzzle would be generated again, i.e., with the same
+    /// seed, difficulty and settings. The stats, including the times, are the
+    /// ones of the first generation. `false` by default.
+    ///
+    /// Useful when a puzzle with a fixed seed is generated repeatedly, like a
+    /// daily puzzle.
+    pub fn cache(mut self, do_cache: bool) -> Self {
+        self.cache = if do_cache {
+            Some(RefCell::new(None))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Configure the minimum number of empty positions left in every group
+    /// so the empty positions are spread over the board. `0` by default.
+    ///
+    /// The minimum is clamped so that all of them add up to at most the empty
+    /// positions of the difficulty. When the puzzle must have a unique
+    /// solution, a group may end up with fewer empty positions if no more can
+    /// be removed.
+    pub fn min_empties_per_box(mut self, n: usize) -> Self {
+        self.min_empties_per_box = n;
+        self
+    }
+
+    /// Configure the symmetry of the pattern of empty positions. Not
+    /// enforced by default.
+    ///
+    /// With [Symmetry::None] the puzzle is rejected if it happens to be
+    /// symmetric, and some removed positions are given back until no
+    /// symmetry is left. [PuzzleStats::asymmetric] tells if it succeeded.
+    ///
+    /// With any other symmetry, values are removed in pairs of mirrored
+    /// positions, and a pair is skipped if removing it would break the
+    /// uniqueness of the solution, so the pattern is always symmetric. A
+    /// position that is its own mirror is removed alone.
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = Some(symmetry);
+        self
+    }
+
+    /// Configure if the puzzle must not be solvable only with naked and
+    /// hidden singles, so it needs harder techniques or guessing. `false` by
+    /// default.
+    ///
+    /// The values are removed again from the same solution until the puzzle
+    /// needs it, up to 32 times. [PuzzleStats::requires_backtracking] tells if
+    /// it succeeded. Easy puzzles rarely do.
+    pub fn require_backtracking(mut self, require: bool) -> Self {
+        self.require_backtracking = require;
+        self
+    }
+
+    /// Configure if the puzzle must not have any naked single at the start,
+    /// so the first move already needs hidden singles or harder techniques.
+    /// `false` by default.
+    ///
+    /// Like [Generator::require_backtracking], the values are removed again
+    /// until the puzzle meets it. [PuzzleStats::no_trivial_start] tells if it
+    /// succeeded.
+    pub fn no_trivial_start(mut self, no_trivial: bool) -> Self {
+        self.no_trivial_start = no_trivial;
+        self
+    }
+
+    /// Configure if the time taken by the generation is measured in
+    /// [PuzzleStats::times]. If not, both durations are zero, so the same
+    /// seed always generates the exact same output. `true` by default.
+    pub fn measure_time(mut self, measure: bool) -> Self {
+        self.measure_time = measure;
+        self
+    }
+
+    /// Configure the number of given positions of the puzzle, instead of the
+    /// empty positions of the difficulty. The difficulty is still set in
+    /// [PuzzleStats::difficulty].
+    ///
+    /// When the puzzle must have a unique solution, low counts may not be
+    /// reachable, then the values are removed until no more can be and
+    /// [PuzzleStats::empty_positions] tells the real count.
+    pub fn with_clue_count(mut self, clues: usize) -> Self {
+        self.clue_count = Some(clues);
+        self
+    }
+
+    /// Configure the empty positions of the puzzles of each difficulty,
+    /// instead of [Difficulty::default_blanks]. Counts over the size of the
+    /// board empty every position. [Generator::with_clue_count] takes
+    /// precedence over these.
+    pub fn with_difficulty_blanks(
+        mut self,
+        easy: usize,
+        normal: usize,
+        hard: usize,
+        insane: usize,
+    ) -> Self {
+        self.difficulty_blanks = [easy, normal, hard, insane].map(|blanks| blanks.min(SIZE));
+        self
+    }
+
+    /// Configure if the puzzle must be minimal: removing any of its given
+    /// positions would make it have more than one solution. Values are
+    /// removed over the empty positions of the difficulty or
+    /// [Generator::with_clue_count] until no more can be. `false` by default.
+    ///
+    /// Only with a unique solution. With a symmetry, the values are removed
+    /// in mirrored pairs, so some single value may still be removable.
+    /// [PuzzleStats::is_minimal] tells if it is.
+    pub fn minimal(mut self, minimal: bool) -> Self {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Configure the pattern of empty positions of the puzzle: the positions
+    /// that are `true` in the template, by rows, are the only ones removed.
+    /// Useful for decorative patterns, like letters or shapes. Not used by
+    /// default.
+    ///
+    /// The template replaces the empty positions of the difficulty,
+    /// [Generator::with_clue_count], [Generator::min_empties_per_box],
+    /// [Generator::with_symmetry] and [Generator::minimal]. When the puzzle
+    /// must have a unique solution, some positions of the template may have to
+    /// keep their value, [PuzzleStats::kept_from_template] tells which ones.
+    pub fn with_template(mut self, template: [[bool; N2]; N2]) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Configure if the puzzle must be graded with the difficulty it was
+    /// generated for, by the techniques needed to solve it (see
+    /// [SudokuBoard::grade]) and not only by its number of empty positions.
+    /// `false` by default.
+    ///
+    /// Like [Generator::require_backtracking], the values are removed again
+    /// until the grade matches. [PuzzleStats::graded] tells if it succeeded
+    /// and [PuzzleStats::techniques_used] why.
+    pub fn grade_difficulty(mut self, grade: bool) -> Self {
+        self.grade_difficulty = grade;
+        self
+    }
+
+    /// Configure the range, both ends included, where the
+    /// [SudokuBoard::difficulty_score] of the puzzle must be. Not used by
+    /// default.
+    ///
+    /// Like [Generator::require_backtracking], the values are removed again
+    /// until the score is in the range, so it should be reachable with the
+    /// empty positions of the difficulty. [PuzzleStats::in_target_score]
+    /// tells if it succeeded.
+    pub fn with_target_score(mut self, min: f64, max: f64) -> Self {
+        self.target_score = Some((min, max));
+        self
+    }
 }
 
 impl Default for Generator {
@@ -270,6 +1185,23 @@ impl Default for Generator {
             count_solutions: false,
             max_count_solutions: 256,
             show_solution: false,
+            min_empties_per_box: 0,
+            symmetry: None,
+            require_backtracking: false,
+            no_trivial_start: false,
+            measure_time: true,
+            grade_difficulty: false,
+            target_score: None,
+            clue_count: None,
+            difficulty_blanks: [
+                Difficulty::Easy.default_blanks(),
+                Difficulty::Normal.default_blanks(),
+                Difficulty::Hard.default_blanks(),
+                Difficulty::Insane.default_blanks(),
+            ],
+            minimal: false,
+            template: None,
+            cache: None,
         }
     }
 }
@@ -281,6 +1213,9 @@ pub enum GeneratorDifficulty {
     Given(Difficulty),
     /// Random [Difficulty] each time the [Generator::generate] function is called.
     Random,
+    /// Random [Difficulty] chosen with weights and the seed, see
+    /// [Generator::with_weighted_difficulty].
+    Weighted([(Difficulty, f64); 4]),
 }
 
 impl GeneratorDifficulty {
@@ -305,11 +1240,13 @@ impl fmt::Display for GeneratorDifficulty {
         if f.alternate() {
             match self {
                 GeneratorDifficulty::Random => write!(f, "random"),
+                GeneratorDifficulty::Weighted(_) => write!(f, "weighted"),
                 GeneratorDifficulty::Given(d) => write!(f, "{:#}", d),
             }
         } else {
             match self {
                 GeneratorDifficulty::Random => write!(f, "Random"),
+                GeneratorDifficulty::Weighted(_) => write!(f, "Weighted"),
                 GeneratorDifficulty::Given(d) => write!(f, "{}", d),
             }
         }
@@ -317,8 +1254,10 @@ impl fmt::Display for GeneratorDifficulty {
 }
 
 /// Difficulty of the puzzles. Currently only changes the number
-/// of empty positions.
-#[derive(Clone, Debug)]
+/// of empty positions. Ordered from the easiest to the hardest, so
+/// `difficulty >= Difficulty::Hard` selects the hard and insane ones.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum Difficulty {
     Easy,
@@ -333,6 +1272,40 @@ impl Difficulty {
         &["easy", "normal", "hard", "insane"]
     }
 
+    /// Empty positions the [Generator] aims for with this difficulty, unless
+    /// configured with [Generator::with_difficulty_blanks].
+    pub const fn default_blanks(&self) -> usize {
+        match self {
+            Difficulty::Easy => 25,
+            Difficulty::Normal => 35,
+            Difficulty::Hard => 50,
+            Difficulty::Insane => 64,
+        }
+    }
+
+    /// Ranges of empty positions of each difficulty level. The generator
+    /// aims for 25, 35, 50 and 64 empty positions, but with a unique solution
+    /// insane puzzles usually end up with 54 to 58, so the bounds are not
+    /// centered around those numbers.
+    pub fn difficulty_bucket_bounds() -> [(Difficulty, Range<usize>); 4] {
+        [
+            (Difficulty::Easy, 0..30),
+            (Difficulty::Normal, 30..43),
+            (Difficulty::Hard, 43..53),
+            (Difficulty::Insane, 53..SIZE + 1),
+        ]
+    }
+
+    /// Classifies a board by its number of empty positions, with the bounds
+    /// of [Difficulty::difficulty_bucket_bounds].
+    pub fn from_empty_count(empty_positions: usize) -> Difficulty {
+        Difficulty::difficulty_bucket_bounds()
+            .iter()
+            .find(|(_, range)| range.contains(&empty_positions))
+            .map(|(difficulty, _)| difficulty.clone())
+            .unwrap_or(Difficulty::Insane)
+    }
+
     /// Returns the str representation of the difficulty
     pub const fn as_str(&self) -> &'static str {
         match self {
@@ -376,6 +1349,7 @@ impl fmt::Display for Difficulty {
 }
 
 #[cfg(test)]
+#[allow(clippy::manual_contains)]
 mod tests {
     use super::*;
 
@@ -390,6 +1364,77 @@ mod tests {
         assert_eq!(puzzle.solution.unwrap(), solution);
     }
 
+    #[test]
+    fn same_puzzle_with_seed() {
+        let generate = |seed: &str| {
+            SudokuPuzzle::prepare()
+                .with_random_difficulty()
+                .with_seed(seed)
+                .show_solution(true)
+                .count_solutions(true)
+                .measure_time(false)
+                .generate()
+        };
+        for seed in &["SAME", "SEED", "SHARED"] {
+            assert_eq!(generate(seed), generate(seed));
+        }
+
+        let difficulties: Vec<Difficulty> = (0..10)
+            .map(|i| generate(&i.to_string()).stats.difficulty)
+            .collect();
+        assert!(difficulties.iter().any(|d| *d != difficulties[0]));
+    }
+
+    #[test]
+    fn min_empties_per_box() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_given_difficulty(Difficulty::Easy)
+            .with_seed("TEST")
+            .min_empties_per_box(4)
+            .generate()
+            .puzzle;
+
+        let mut group_empties = [0; N2];
+        for (pos, &val) in puzzle.iter().enumerate() {
+            if val == 0 {
+                group_empties[group_index(pos)] += 1;
+            }
+        }
+        assert!(group_empties.iter().all(|&empties| empties >= 2));
+        assert_eq!(puzzle.count_solutions(2), 1);
+        // easy puzzles have 25 empty positions, so at most 2 per group
+        assert_eq!(group_empties.iter().sum::<usize>(), 25);
+    }
+
+    #[test]
+    fn cached_generation() {
+        let generator = SudokuPuzzle::prepare()
+            .with_seed("TEST")
+            .show_solution(true)
+            .cache(true);
+        let first = generator.generate();
+        let second = generator.generate();
+        assert_eq!(first.puzzle, second.puzzle);
+        assert_eq!(first.stats.times, second.stats.times);
+
+        let other = generator
+            .clone()
+            .with_given_difficulty(Difficulty::Hard)
+            .generate();
+        assert_ne!(first.puzzle, other.puzzle);
+        assert_eq!(first.solution, other.solution);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn clone_puzzle() {
+        let puzzle = SudokuPuzzle::prepare().show_solution(true).generate();
+        let cloned = puzzle.clone();
+        assert_eq!(puzzle.puzzle(), cloned.puzzle());
+        assert_eq!(puzzle.solution(), cloned.solution());
+        assert_eq!(puzzle.stats().seed, cloned.stats().seed);
+    }
+
     #[test]
     fn all_difficulties_strings() {
         for &d_str in Difficulty::get_all() {
@@ -398,6 +1443,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn difficulty_order() {
+        let all: Vec<Difficulty> = Difficulty::get_all()
+            .iter()
+            .map(|&name| Difficulty::try_from(name).unwrap())
+            .collect();
+        assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let hard_or_above: std::collections::HashSet<Difficulty> = all
+            .into_iter()
+            .filter(|difficulty| *difficulty >= Difficulty::Hard)
+            .collect();
+        assert_eq!(hard_or_above.len(), 2);
+        assert!(hard_or_above.contains(&Difficulty::Insane));
+        assert!(!hard_or_above.contains(&Difficulty::Normal));
+    }
+
     #[test]
     fn generator_difficulty_and_difficulty() {
         let g_ds = GeneratorDifficulty::get_all();
@@ -410,4 +1472,454 @@ mod tests {
         }
         assert!(g_ds.iter().any(|&g_d| g_d == "random"));
     }
+
+    #[test]
+    fn asymmetric() {
+        let generator = SudokuPuzzle::prepare().with_symmetry(Symmetry::None);
+        for seed in &["ASYM", "TEST", "SUDOKU"] {
+            let puzzle = generator.clone().with_seed(seed).generate();
+            assert_eq!(puzzle.stats.asymmetric, Some(true));
+            assert!(puzzle.puzzle.detect_symmetry().is_empty());
+            assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+        }
+
+        let puzzle = SudokuPuzzle::prepare().with_seed("ASYM").generate();
+        assert_eq!(puzzle.stats.asymmetric, None);
+    }
+
+    #[test]
+    fn grade_difficulty() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let puzzle = SudokuPuzzle::prepare()
+                .with_given_difficulty(difficulty.clone())
+                .with_seed("GRADED")
+                .grade_difficulty(true)
+                .generate();
+            if puzzle.stats.graded == Some(true) {
+                assert_eq!(puzzle.puzzle.grade(), difficulty);
+            }
+            let mut techniques = puzzle.stats.techniques_used.clone();
+            techniques.sort_unstable();
+            techniques.dedup();
+            assert_eq!(techniques, puzzle.stats.techniques_used);
+        }
+
+        let puzzle = SudokuPuzzle::prepare()
+            .with_given_difficulty(Difficulty::Easy)
+            .with_seed("GRADED")
+            .grade_difficulty(true)
+            .generate();
+        assert_eq!(puzzle.stats.graded, Some(true));
+        assert_eq!(puzzle.stats.techniques_used, vec![Technique::NakedSingle]);
+        let puzzle = SudokuPuzzle::prepare().with_seed("GRADED").generate();
+        assert_eq!(puzzle.stats.graded, None);
+    }
+
+    #[test]
+    fn with_clue_count() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("CLUES")
+            .with_clue_count(30)
+            .generate();
+        assert_eq!(puzzle.puzzle.clue_count(), 30);
+        assert_eq!(puzzle.stats.empty_positions, SIZE - 30);
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+
+        // too few to keep a unique solution
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("CLUES")
+            .with_clue_count(10)
+            .generate();
+        assert!(puzzle.puzzle.clue_count() > 10);
+        assert_eq!(puzzle.stats.empty_positions, puzzle.puzzle.empty_count());
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("CLUES")
+            .with_clue_count(SIZE + 1)
+            .generate();
+        assert_eq!(puzzle.stats.empty_positions, 0);
+    }
+
+    #[test]
+    fn with_difficulty_blanks() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("BLANKS")
+            .with_given_difficulty(Difficulty::Hard)
+            .with_difficulty_blanks(20, 30, 40, 50)
+            .generate();
+        assert_eq!(puzzle.stats.empty_positions, 40);
+        assert_eq!(puzzle.puzzle.empty_count(), 40);
+
+        let default = SudokuPuzzle::prepare()
+            .with_seed("BLANKS")
+            .with_given_difficulty(Difficulty::Hard)
+            .generate();
+        assert_eq!(
+            default.stats.empty_positions,
+            Difficulty::Hard.default_blanks()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn rng_trace() {
+        let generator = SudokuPuzzle::prepare().with_seed("TRACE");
+        let a = generator.generate();
+        let b = generator.generate();
+        assert!(!a.stats.rng_trace.is_empty());
+        assert_eq!(a.stats.rng_trace, b.stats.rng_trace);
+
+        let c = generator.with_seed("OTHER TRACE").generate();
+        assert_ne!(a.stats.rng_trace, c.stats.rng_trace);
+    }
+
+    #[test]
+    fn with_target_score() {
+        let generator = SudokuPuzzle::prepare()
+            .with_seed("SCORE")
+            .with_given_difficulty(Difficulty::Hard);
+        let puzzle = generator.generate();
+        assert_eq!(puzzle.stats.score, puzzle.puzzle.difficulty_score());
+        assert_eq!(puzzle.stats.in_target_score, None);
+
+        let puzzle = generator.clone().with_target_score(52.0, 60.0).generate();
+        assert_eq!(puzzle.stats.in_target_score, Some(true));
+        assert!((52.0..=60.0).contains(&puzzle.stats.score));
+
+        let puzzle = generator.with_target_score(1000.0, 2000.0).generate();
+        assert_eq!(puzzle.stats.in_target_score, Some(false));
+    }
+
+    #[test]
+    fn minimal() {
+        let generator = SudokuPuzzle::prepare().with_seed("MINIMAL");
+        let puzzle = generator.clone().minimal(true).generate();
+        assert!(puzzle.stats.is_minimal);
+        assert!(puzzle.puzzle.is_minimal());
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.stats.empty_positions, puzzle.puzzle.empty_count());
+
+        let puzzle = generator.with_given_difficulty(Difficulty::Easy).generate();
+        assert!(!puzzle.stats.is_minimal);
+
+        for seed in &["A", "B", "C", "D"] {
+            let puzzle = SudokuPuzzle::prepare()
+                .with_given_difficulty(Difficulty::Insane)
+                .with_seed(seed)
+                .generate();
+            assert_eq!(puzzle.stats.is_minimal, puzzle.puzzle.is_minimal());
+        }
+    }
+
+    #[test]
+    fn with_template() {
+        // a frame around the board and the center
+        let mut template = [[false; N2]; N2];
+        for (row, line) in template.iter_mut().enumerate() {
+            for (col, blank) in line.iter_mut().enumerate() {
+                *blank = row == 0 || row == N2 - 1 || col == 0 || col == N2 - 1 || row == col;
+            }
+        }
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("TEMPLATE")
+            .with_template(template)
+            .generate();
+        for pos in 0..SIZE {
+            let blank = template[pos / N2][pos % N2];
+            let kept = puzzle.stats.kept_from_template.contains(&pos);
+            assert_eq!(puzzle.puzzle[pos] == 0, blank && !kept);
+        }
+        assert_eq!(puzzle.stats.empty_positions, puzzle.puzzle.empty_count());
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+
+        // the whole board can not be empty with a unique solution
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("TEMPLATE")
+            .with_template([[true; N2]; N2])
+            .generate();
+        assert!(!puzzle.stats.kept_from_template.is_empty());
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+
+        let puzzle = SudokuPuzzle::prepare().with_seed("TEMPLATE").generate();
+        assert!(puzzle.stats.kept_from_template.is_empty());
+    }
+
+    #[test]
+    fn generate_batch() {
+        let generator = SudokuPuzzle::prepare().with_seed("BATCH");
+        let batch = generator.generate_batch(4);
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch[0].puzzle, generator.generate().puzzle);
+        assert_eq!(batch[2].stats.seed, "BATCH-2");
+        for (i, puzzle) in batch.iter().enumerate() {
+            assert!(batch[i + 1..]
+                .iter()
+                .all(|other| other.puzzle != puzzle.puzzle));
+            let again = generator.clone().with_seed(&puzzle.stats.seed).generate();
+            assert_eq!(again.puzzle, puzzle.puzzle);
+        }
+        let batch_again = generator.generate_batch(4);
+        for (a, b) in batch.iter().zip(&batch_again) {
+            assert_eq!(a.puzzle, b.puzzle);
+        }
+
+        #[cfg(feature = "std")]
+        assert_eq!(SudokuPuzzle::prepare().generate_batch(3).len(), 3);
+        assert!(generator.generate_batch(0).is_empty());
+    }
+
+    #[test]
+    fn measure_time() {
+        let generator = SudokuPuzzle::prepare()
+            .with_seed("TIME")
+            .measure_time(false);
+        let puzzle = generator.generate();
+        assert_eq!(puzzle.stats.times, (Duration::ZERO, Duration::ZERO));
+        assert_eq!(
+            format!("{:#}", puzzle),
+            format!("{:#}", generator.generate())
+        );
+
+        let measured = SudokuPuzzle::prepare().with_seed("TIME").generate();
+        assert_eq!(measured.puzzle, puzzle.puzzle);
+    }
+
+    #[test]
+    fn symmetric() {
+        for &symmetry in &Symmetry::all()[1..] {
+            let puzzle = SudokuPuzzle::prepare()
+                .with_symmetry(symmetry)
+                .with_seed("SYMMETRIC")
+                .min_empties_per_box(2)
+                .generate();
+            assert!(puzzle.puzzle.has_symmetry(symmetry));
+            assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+            assert_eq!(puzzle.stats.empty_positions, puzzle.puzzle.empty_count());
+        }
+    }
+
+    #[test]
+    fn verify_seed() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("VERIFY")
+            .with_given_difficulty(Difficulty::Hard)
+            .generate();
+        let board = puzzle.puzzle;
+
+        assert!(super::verify_seed("VERIFY", Difficulty::Hard, true, &board));
+        assert!(!super::verify_seed(
+            "VERIFY",
+            Difficulty::Easy,
+            true,
+            &board
+        ));
+        assert!(!super::verify_seed("OTHER", Difficulty::Hard, true, &board));
+        assert!(!super::verify_seed(
+            "VERIFY",
+            Difficulty::Hard,
+            false,
+            &board
+        ));
+    }
+
+    #[test]
+    fn share_code() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("SHARE")
+            .with_given_difficulty(Difficulty::Hard)
+            .generate();
+        let code = puzzle.share_code();
+        assert!(code.starts_with("12"));
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+
+        let shared = SudokuPuzzle::from_share_code(&code).unwrap();
+        assert_eq!(shared.puzzle, puzzle.puzzle);
+        assert_eq!(shared.stats.seed, "SHARE");
+        assert_eq!(shared.stats.difficulty, Difficulty::Hard);
+        assert_eq!(shared.stats.empty_positions, puzzle.stats.empty_positions);
+
+        assert!(SudokuPuzzle::from_share_code("").is_err());
+        assert!(SudokuPuzzle::from_share_code(&code.replacen('1', "9", 1)).is_err());
+        assert!(SudokuPuzzle::from_share_code(&code[..40]).is_err());
+        assert!(SudokuPuzzle::from_share_code(&(code.clone() + "1")).is_err());
+    }
+
+    #[test]
+    fn generator_from_share_code() {
+        for (i, &difficulty) in Difficulty::get_all().iter().enumerate() {
+            for &unique in &[true, false] {
+                let puzzle = SudokuPuzzle::prepare()
+                    .with_seed(&format!("SHARE{}", i))
+                    .with_given_difficulty(Difficulty::try_from(difficulty).unwrap())
+                    .unique_solution(unique)
+                    .generate();
+                let generator = Generator::from_share_code(&puzzle.share_code()).unwrap();
+                let generated = generator.generate();
+                assert_eq!(generated.puzzle, puzzle.puzzle);
+                assert_eq!(generated.stats.difficulty, puzzle.stats.difficulty);
+            }
+        }
+        assert!(Generator::from_share_code("").is_err());
+    }
+
+    #[test]
+    fn csv_columns() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("CSV")
+            .with_given_difficulty(Difficulty::Hard)
+            .generate();
+        assert_eq!(
+            SudokuPuzzle::csv_head_with(CsvColumn::all()),
+            SudokuPuzzle::csv_head()
+        );
+        let mut all = String::new();
+        puzzle.write_csv_fields(&mut all, CsvColumn::all()).unwrap();
+        assert_eq!(all, format!("{:#}", puzzle));
+
+        let columns = [
+            CsvColumn::Difficulty,
+            CsvColumn::Puzzle,
+            CsvColumn::Solution,
+        ];
+        assert_eq!(
+            SudokuPuzzle::csv_head_with(&columns),
+            "difficulty,puzzle,solution"
+        );
+        let mut line = String::new();
+        puzzle.write_csv_fields(&mut line, &columns).unwrap();
+        assert_eq!(line, format!("hard,{:#},", puzzle.puzzle));
+
+        for &column in CsvColumn::all() {
+            assert_eq!(CsvColumn::try_from(column.as_str()), Ok(column));
+        }
+        assert!(CsvColumn::try_from("score").is_err());
+    }
+
+    #[test]
+    fn difficulty_buckets() {
+        let bounds = Difficulty::difficulty_bucket_bounds();
+        assert_eq!(bounds[0].1.start, 0);
+        assert_eq!(bounds[3].1.end, SIZE + 1);
+        for w in bounds.windows(2) {
+            assert_eq!(w[0].1.end, w[1].1.start);
+        }
+
+        for (difficulty, _) in bounds.iter() {
+            for seed in &["BUCKET", "TEST"] {
+                let puzzle = SudokuPuzzle::prepare()
+                    .with_seed(seed)
+                    .with_given_difficulty(difficulty.clone())
+                    .generate();
+                assert_eq!(
+                    Difficulty::from_empty_count(puzzle.stats.empty_positions),
+                    *difficulty
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn require_backtracking() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("BACKTRACK")
+            .with_given_difficulty(Difficulty::Insane)
+            .require_backtracking(true)
+            .generate();
+        assert_eq!(puzzle.stats.requires_backtracking, Some(true));
+        assert_eq!(puzzle.puzzle.suggested_order(), None);
+        assert_eq!(puzzle.puzzle.count_solutions(2), 1);
+
+        let puzzle = SudokuPuzzle::prepare().with_seed("BACKTRACK").generate();
+        assert_eq!(puzzle.stats.requires_backtracking, None);
+    }
+
+    #[test]
+    fn no_trivial_start() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("NO TRIVIAL")
+            .with_given_difficulty(Difficulty::Hard)
+            .no_trivial_start(true)
+            .generate();
+        assert_eq!(puzzle.stats.no_trivial_start, Some(true));
+        assert!(puzzle
+            .puzzle
+            .resolvable_by(Technique::NakedSingle)
+            .is_empty());
+    }
+
+    #[test]
+    fn solution_or_solve() {
+        let with_solution = SudokuPuzzle::prepare()
+            .with_seed("LAZY")
+            .show_solution(true)
+            .generate();
+        let mut puzzle = SudokuPuzzle::prepare().with_seed("LAZY").generate();
+        assert!(puzzle.solution().is_none());
+
+        assert_eq!(
+            puzzle.solution_or_solve(),
+            with_solution.solution().unwrap()
+        );
+        assert_eq!(puzzle.solution(), with_solution.solution());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn weighted_difficulty() {
+        let only_hard = [
+            (Difficulty::Easy, 0.0),
+            (Difficulty::Normal, 0.0),
+            (Difficulty::Hard, 1.0),
+            (Difficulty::Insane, 0.0),
+        ];
+        let puzzle = SudokuPuzzle::prepare()
+            .with_weighted_difficulty(only_hard)
+            .generate();
+        assert_eq!(puzzle.stats.difficulty, Difficulty::Hard);
+
+        let weights = [
+            (Difficulty::Easy, 1.0),
+            (Difficulty::Normal, 2.0),
+            (Difficulty::Hard, 3.0),
+            (Difficulty::Insane, 4.0),
+        ];
+        let generator = SudokuPuzzle::prepare()
+            .with_weighted_difficulty(weights)
+            .with_seed("WEIGHTED");
+        assert_eq!(
+            generator.generate().stats.difficulty,
+            generator.generate().stats.difficulty
+        );
+
+        let invalid = [
+            (Difficulty::Easy, 0.0),
+            (Difficulty::Normal, 0.0),
+            (Difficulty::Hard, 0.0),
+            (Difficulty::Insane, 0.0),
+        ];
+        let puzzle = SudokuPuzzle::prepare()
+            .with_weighted_difficulty(invalid)
+            .generate();
+        assert_eq!(puzzle.stats.difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let puzzle = SudokuPuzzle::prepare()
+            .with_seed("SERDE")
+            .show_solution(true)
+            .generate();
+
+        let json = serde_json::to_string(&puzzle).unwrap();
+        let deserialized: SudokuPuzzle = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.puzzle, puzzle.puzzle);
+        assert_eq!(deserialized.solution, puzzle.solution);
+        assert_eq!(deserialized.stats.seed, "SERDE");
+        assert_eq!(deserialized.stats.difficulty, Difficulty::Normal);
+        assert_eq!(deserialized.stats.times, puzzle.stats.times);
+    }
 }